@@ -0,0 +1,71 @@
+//! Throughput of many users' requests in flight at once against a fake go-amizone that takes
+//! 20ms per request. `serialized` reproduces the old single `Mutex` around the client for
+//! comparison.
+//!
+//! Run with `cargo bench -p amizone --features test-support`
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use amizone::api::{
+    backend::AmizoneBackend, client::UserClient, new_amizone_connection,
+    user::credentials::Credentials, ConnectionConfig,
+};
+use amizone::testing::{FakeAmizone, SAMPLE_PASSWORD, SAMPLE_USERNAME};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::future::join_all;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+
+const LATENCY: Duration = Duration::from_millis(20);
+const CONCURRENT_COMMANDS: usize = 64;
+
+fn concurrent_commands(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    // The channels spawn their background tasks on the runtime they're created in
+    let _guard = runtime.enter();
+    let fake = FakeAmizone::with_sample_data().with_latency(LATENCY);
+    let server = runtime.block_on(fake.serve()).unwrap();
+    let auth = Credentials::new(SAMPLE_USERNAME, SAMPLE_PASSWORD)
+        .get_auth()
+        .unwrap();
+
+    let mut group = c.benchmark_group("concurrent_commands");
+    group.throughput(Throughput::Elements(CONCURRENT_COMMANDS as u64));
+    group.sample_size(20);
+
+    for pool_size in [1, 4] {
+        let connection = new_amizone_connection(ConnectionConfig {
+            pool_size,
+            ..ConnectionConfig::new(server.url())
+        })
+        .unwrap();
+        let client = UserClient::new(auth.clone(), connection);
+
+        group.bench_with_input(
+            BenchmarkId::new("shared", pool_size),
+            &client,
+            |b, client| {
+                b.to_async(&runtime)
+                    .iter(|| join_all((0..CONCURRENT_COMMANDS).map(|_| client.get_attendance())))
+            },
+        );
+    }
+
+    let connection = new_amizone_connection(ConnectionConfig::new(server.url())).unwrap();
+    let serialized = Arc::new(Mutex::new(UserClient::new(auth, connection)));
+
+    group.bench_function("serialized", |b| {
+        b.to_async(&runtime).iter(|| {
+            join_all((0..CONCURRENT_COMMANDS).map(|_| {
+                let client = serialized.clone();
+                async move { client.lock().await.get_attendance().await }
+            }))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_commands);
+criterion_main!(benches);
@@ -1,6 +1,9 @@
 fn main() {
+    // Server stubs are only needed for the fake go-amizone in `testing`
+    let build_server = std::env::var_os("CARGO_FEATURE_TEST_SUPPORT").is_some();
+
     tonic_build::configure()
-        .build_server(false)
+        .build_server(build_server)
         .protoc_arg("--experimental_allow_proto3_optional")
         .include_file("_includes.rs")
         .compile(
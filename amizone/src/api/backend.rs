@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use super::types::*;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use futures::{StreamExt, TryStreamExt};
+
+/// How many days [`AmizoneBackend::get_schedule_range`] fetches at once
+pub const SCHEDULE_RANGE_CONCURRENCY: usize = 4;
+
+/// The longest range [`AmizoneBackend::get_schedule_range`] accepts, in days
+pub const MAX_SCHEDULE_RANGE: i64 = 62;
+
+/// How many semesters [`AmizoneBackend::get_exam_results`] fetches at once
+pub const EXAM_RESULTS_CONCURRENCY: usize = 4;
+
+/// Every RPC the bot makes against go-amizone, on behalf of a single user.
+///
+/// [`UserClient`](super::client::UserClient) is the real implementation, anything else
+/// (an in-memory fake for tests, a different backend altogether) can be swapped in by
+/// implementing this trait and handing out an [`AmizoneClient`].
+#[async_trait]
+pub trait AmizoneBackend: Send + Sync {
+    async fn get_attendance(&self) -> Result<Vec<AttendanceRecord>>;
+
+    /// Returns (title, exams)
+    async fn get_exam_schedule(&self) -> Result<(String, Vec<ScheduledExam>)>;
+
+    async fn get_semesters(&self) -> Result<Vec<Semester>>;
+
+    async fn get_current_courses(&self) -> Result<Vec<Course>>;
+
+    async fn get_courses(&self, num: usize) -> Result<Vec<Course>>;
+
+    async fn get_exam_result(&self, num: usize) -> Result<ExamResultRecords>;
+
+    async fn get_current_exam_result(&self) -> Result<ExamResultRecords>;
+
+    async fn get_user_profile(&self) -> Result<AmizoneProfile>;
+
+    async fn get_wifi_mac_info(&self) -> Result<WifiMacInfo>;
+
+    async fn register_wifi_mac(&self, addr: &str) -> Result<()>;
+
+    async fn deregister_wifi_mac(&self, addr: &str) -> Result<()>;
+
+    /// Returns the number of faculties the feedback was filled for
+    async fn fill_faculty_feedback(
+        &self,
+        rating: i32,
+        query_rating: i32,
+        comment: &str,
+    ) -> Result<i32>;
+
+    async fn get_class_schedule(&self, date: Date) -> Result<Vec<ScheduledClass>>;
+
+    /// Class schedule of every day from `start` to `end`, both inclusive, fetched a few days
+    /// at a time. Days without classes (holidays, weekends) map to an empty `Vec`.
+    async fn get_schedule_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<BTreeMap<NaiveDate, Vec<ScheduledClass>>> {
+        if end < start {
+            return Err(AmizoneApiError::invalid_argument(
+                "Schedule range ends before it starts",
+            ));
+        }
+        if (end - start).num_days() >= MAX_SCHEDULE_RANGE {
+            return Err(AmizoneApiError::invalid_argument(format!(
+                "Schedule range can't be longer than {} days",
+                MAX_SCHEDULE_RANGE
+            )));
+        }
+
+        futures::stream::iter(start.iter_days().take_while(|day| *day <= end))
+            .map(|day| async move {
+                let classes = self.get_class_schedule(day.into()).await?;
+                Ok::<_, AmizoneApiError>((day, classes))
+            })
+            .buffer_unordered(SCHEDULE_RANGE_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Exam results of every semester in `semesters`, fetched a few at a time. A semester
+    /// that fails doesn't fail the rest, its error is kept in its place.
+    async fn get_exam_results(
+        &self,
+        semesters: &[usize],
+    ) -> BTreeMap<usize, Result<ExamResultRecords>> {
+        futures::stream::iter(semesters.iter().copied())
+            .map(|num| async move { (num, self.get_exam_result(num).await) })
+            .buffer_unordered(EXAM_RESULTS_CONCURRENCY)
+            .collect()
+            .await
+    }
+}
@@ -0,0 +1,544 @@
+//! Optional caching of go-amizone responses, shared between every user.
+//!
+//! Wrap a backend in a [`CachingBackend`] to serve repeated reads from a [`ResponseCache`]
+//! for as long as the TTL of their RPC, see [`CacheConfig`]. Data that can't change anymore,
+//! the class schedule of a day before yesterday or the courses and results of a past semester,
+//! is kept for longer. Any mutating call drops everything cached for that user.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::backend::AmizoneBackend;
+use super::connection::Rpc;
+use super::types::*;
+use crate::config::{env_var, parse_secs};
+
+/// How long responses of each RPC are cached for, and how many are kept at most
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Least recently used responses are evicted beyond this
+    pub max_entries: usize,
+    /// RPCs without a TTL aren't cached
+    ttls: HashMap<Rpc, Duration>,
+    /// For responses that can't change anymore, like the schedule of a day before yesterday
+    pub immutable_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        const MINUTE: Duration = Duration::from_secs(60);
+        const HOUR: Duration = Duration::from_secs(60 * 60);
+
+        Self {
+            max_entries: 10_000,
+            ttls: HashMap::from([
+                (Rpc::GetAttendance, 10 * MINUTE),
+                (Rpc::GetClassSchedule, 15 * MINUTE),
+                (Rpc::GetExamSchedule, HOUR),
+                (Rpc::GetSemesters, 24 * HOUR),
+                (Rpc::GetCourses, 10 * MINUTE),
+                (Rpc::GetCurrentCourses, 10 * MINUTE),
+                (Rpc::GetExamResult, HOUR),
+                (Rpc::GetCurrentExamResult, HOUR),
+                (Rpc::GetUserProfile, 24 * HOUR),
+                (Rpc::GetWifiMacInfo, 10 * MINUTE),
+            ]),
+            immutable_ttl: 7 * 24 * HOUR,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// `None` when caching is disabled, which it is unless `AMIZONE_CACHE_SIZE` is set to the
+    /// maximum number of cached responses.
+    ///
+    /// TTLs can be overridden in seconds through `AMIZONE_CACHE_TTLS`, eg.
+    /// `GetAttendance=300,GetWifiMacInfo=0`, 0 stops the RPC from being cached.
+    pub fn from_env() -> std::result::Result<Option<Self>, String> {
+        let Some(size) = env_var("AMIZONE_CACHE_SIZE") else {
+            return Ok(None);
+        };
+
+        let mut config = Self {
+            max_entries: size
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid `AMIZONE_CACHE_SIZE`: {}", size))?,
+            ..Default::default()
+        };
+
+        if config.max_entries == 0 {
+            return Ok(None);
+        }
+
+        if let Some(ttls) = env_var("AMIZONE_CACHE_TTLS") {
+            for entry in ttls.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let (rpc, secs) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected `Rpc=seconds`, got `{}`", entry))?;
+                let rpc = rpc.trim().parse()?;
+
+                if secs.trim() == "0" {
+                    config.ttls.remove(&rpc);
+                } else {
+                    config.set_ttl(rpc, parse_secs(secs)?)?;
+                }
+            }
+        }
+
+        Ok(Some(config))
+    }
+
+    pub fn ttl(&self, rpc: Rpc) -> Option<Duration> {
+        self.ttls.get(&rpc).copied()
+    }
+
+    /// Mutating RPCs can't be cached
+    pub fn set_ttl(&mut self, rpc: Rpc, ttl: Duration) -> std::result::Result<(), String> {
+        if !rpc.is_idempotent() {
+            return Err(format!("{:?} can't be cached", rpc));
+        }
+
+        self.ttls.insert(rpc, ttl);
+        Ok(())
+    }
+
+    pub fn disable(&mut self, rpc: Rpc) {
+        self.ttls.remove(&rpc);
+    }
+}
+
+/// Snapshot of the counters of a [`ResponseCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Responses dropped to stay under `max_entries`
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    user: String,
+    rpc: Rpc,
+    /// The arguments of the request, if any
+    request: String,
+}
+
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    expires: Instant,
+    /// Key into `CacheState::recency`
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, Entry>,
+    /// last use -> key, the first entry is the least recently used one
+    recency: BTreeMap<u64, CacheKey>,
+    clock: u64,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.last_used);
+        Some(entry)
+    }
+}
+
+/// Responses of every user, bounded in size. Cheap to share behind an `Arc`.
+pub struct ResponseCache {
+    config: CacheConfig,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+            evictions: AtomicU64::default(),
+        }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.state.lock().unwrap().entries.len(),
+        }
+    }
+
+    /// Drops everything cached for `user`
+    pub fn invalidate_user(&self, user: &str) {
+        let mut state = self.state.lock().unwrap();
+        let stale = state
+            .entries
+            .keys()
+            .filter(|key| key.user == user)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for key in stale {
+            state.remove(&key);
+        }
+    }
+
+    fn get<T: Clone + 'static>(&self, key: &CacheKey) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+
+        let entry = state.entries.get(key)?;
+        if entry.expires <= Instant::now() {
+            state.remove(key);
+            return None;
+        }
+
+        let previous_use = entry.last_used;
+        let value = entry.value.downcast_ref::<T>()?.clone();
+
+        let now = state.tick();
+        state.recency.remove(&previous_use);
+        state.recency.insert(now, key.clone());
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.last_used = now;
+        }
+
+        Some(value)
+    }
+
+    fn insert<T: Send + Sync + 'static>(&self, key: CacheKey, value: T, ttl: Duration) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.remove(&key);
+
+        while state.entries.len() >= self.config.max_entries {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let now = state.tick();
+        state.recency.insert(now, key.clone());
+        state.entries.insert(
+            key,
+            Entry {
+                value: Arc::new(value),
+                expires: Instant::now() + ttl,
+                last_used: now,
+            },
+        );
+    }
+}
+
+/// An [`AmizoneBackend`] serving reads from a [`ResponseCache`] when it can, and from the
+/// wrapped backend otherwise.
+pub struct CachingBackend<B> {
+    inner: B,
+    /// Whose responses these are, responses are never shared between users
+    user: String,
+    cache: Arc<ResponseCache>,
+}
+
+impl<B: AmizoneBackend> CachingBackend<B> {
+    pub fn new(inner: B, user: impl ToString, cache: Arc<ResponseCache>) -> Self {
+        Self {
+            inner,
+            user: user.to_string(),
+            cache,
+        }
+    }
+
+    fn key(&self, rpc: Rpc, request: impl ToString) -> CacheKey {
+        CacheKey {
+            user: self.user.clone(),
+            rpc,
+            request: request.to_string(),
+        }
+    }
+
+    /// The TTL of `rpc`, or the one for immutable data if `immutable`
+    fn ttl(&self, rpc: Rpc, immutable: bool) -> Option<Duration> {
+        let ttl = self.cache.config.ttl(rpc)?;
+        if immutable {
+            Some(self.cache.config.immutable_ttl.max(ttl))
+        } else {
+            Some(ttl)
+        }
+    }
+
+    async fn cached<T, Fut>(
+        &self,
+        rpc: Rpc,
+        request: impl ToString,
+        ttl: Option<Duration>,
+        fetch: Fut,
+    ) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<T>>,
+    {
+        let Some(ttl) = ttl else {
+            return fetch.await;
+        };
+
+        let key = self.key(rpc, request);
+        if let Some(value) = self.cache.get::<T>(&key) {
+            self.cache.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        self.cache.misses.fetch_add(1, Ordering::Relaxed);
+        let value = fetch.await?;
+        self.cache.insert(key, value.clone(), ttl);
+
+        Ok(value)
+    }
+
+    /// Whether semester `num` is over, going by the cached semesters (the latest of which is
+    /// the current one). Unknown if they aren't cached, in which case it's assumed not to be.
+    fn is_past_semester(&self, num: usize) -> bool {
+        self.cache
+            .get::<Vec<Semester>>(&self.key(Rpc::GetSemesters, ""))
+            .and_then(|semesters| {
+                semesters
+                    .iter()
+                    .filter_map(|semester| semester.r#ref.parse::<usize>().ok())
+                    .max()
+            })
+            .is_some_and(|current| num < current)
+    }
+
+    /// Drops the user's cached responses whether the mutation went through or not, a failed
+    /// one could've still been partially applied
+    fn invalidate<T>(&self, result: Result<T>) -> Result<T> {
+        self.cache.invalidate_user(&self.user);
+        result
+    }
+}
+
+/// Whether `date` is before yesterday, in IST. Attendance of yesterday's classes can still be
+/// marked, so its schedule isn't done changing yet.
+fn is_past_date(date: &Date) -> bool {
+    let yesterday = crate::model::today().pred_opt();
+    NaiveDate::try_from(date.clone()).is_ok_and(|date| Some(date) < yesterday)
+}
+
+#[async_trait]
+impl<B: AmizoneBackend> AmizoneBackend for CachingBackend<B> {
+    async fn get_attendance(&self) -> Result<Vec<AttendanceRecord>> {
+        let ttl = self.ttl(Rpc::GetAttendance, false);
+        self.cached(Rpc::GetAttendance, "", ttl, self.inner.get_attendance())
+            .await
+    }
+
+    async fn get_exam_schedule(&self) -> Result<(String, Vec<ScheduledExam>)> {
+        let ttl = self.ttl(Rpc::GetExamSchedule, false);
+        self.cached(
+            Rpc::GetExamSchedule,
+            "",
+            ttl,
+            self.inner.get_exam_schedule(),
+        )
+        .await
+    }
+
+    async fn get_semesters(&self) -> Result<Vec<Semester>> {
+        let ttl = self.ttl(Rpc::GetSemesters, false);
+        self.cached(Rpc::GetSemesters, "", ttl, self.inner.get_semesters())
+            .await
+    }
+
+    async fn get_current_courses(&self) -> Result<Vec<Course>> {
+        let ttl = self.ttl(Rpc::GetCurrentCourses, false);
+        self.cached(
+            Rpc::GetCurrentCourses,
+            "",
+            ttl,
+            self.inner.get_current_courses(),
+        )
+        .await
+    }
+
+    async fn get_courses(&self, num: usize) -> Result<Vec<Course>> {
+        let ttl = self.ttl(Rpc::GetCourses, self.is_past_semester(num));
+        self.cached(Rpc::GetCourses, num, ttl, self.inner.get_courses(num))
+            .await
+    }
+
+    async fn get_exam_result(&self, num: usize) -> Result<ExamResultRecords> {
+        let ttl = self.ttl(Rpc::GetExamResult, self.is_past_semester(num));
+        self.cached(
+            Rpc::GetExamResult,
+            num,
+            ttl,
+            self.inner.get_exam_result(num),
+        )
+        .await
+    }
+
+    async fn get_current_exam_result(&self) -> Result<ExamResultRecords> {
+        let ttl = self.ttl(Rpc::GetCurrentExamResult, false);
+        self.cached(
+            Rpc::GetCurrentExamResult,
+            "",
+            ttl,
+            self.inner.get_current_exam_result(),
+        )
+        .await
+    }
+
+    async fn get_user_profile(&self) -> Result<AmizoneProfile> {
+        let ttl = self.ttl(Rpc::GetUserProfile, false);
+        self.cached(Rpc::GetUserProfile, "", ttl, self.inner.get_user_profile())
+            .await
+    }
+
+    async fn get_wifi_mac_info(&self) -> Result<WifiMacInfo> {
+        let ttl = self.ttl(Rpc::GetWifiMacInfo, false);
+        self.cached(Rpc::GetWifiMacInfo, "", ttl, self.inner.get_wifi_mac_info())
+            .await
+    }
+
+    async fn register_wifi_mac(&self, addr: &str) -> Result<()> {
+        self.invalidate(self.inner.register_wifi_mac(addr).await)
+    }
+
+    async fn deregister_wifi_mac(&self, addr: &str) -> Result<()> {
+        self.invalidate(self.inner.deregister_wifi_mac(addr).await)
+    }
+
+    async fn fill_faculty_feedback(
+        &self,
+        rating: i32,
+        query_rating: i32,
+        comment: &str,
+    ) -> Result<i32> {
+        self.invalidate(
+            self.inner
+                .fill_faculty_feedback(rating, query_rating, comment)
+                .await,
+        )
+    }
+
+    async fn get_class_schedule(&self, date: Date) -> Result<Vec<ScheduledClass>> {
+        let ttl = self.ttl(Rpc::GetClassSchedule, is_past_date(&date));
+        let request = format!("{}-{}-{}", date.year, date.month, date.day);
+        self.cached(
+            Rpc::GetClassSchedule,
+            request,
+            ttl,
+            self.inner.get_class_schedule(date),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(user: &str, request: &str) -> CacheKey {
+        CacheKey {
+            user: user.to_string(),
+            rpc: Rpc::GetCourses,
+            request: request.to_string(),
+        }
+    }
+
+    #[test]
+    fn expiry() {
+        let cache = ResponseCache::new(CacheConfig::default());
+
+        cache.insert(key("a", "1"), 1usize, Duration::ZERO);
+        cache.insert(key("a", "2"), 2usize, Duration::from_secs(60));
+
+        assert_eq!(cache.get::<usize>(&key("a", "1")), None);
+        assert_eq!(cache.get::<usize>(&key("a", "2")), Some(2));
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = ResponseCache::new(CacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        let ttl = Duration::from_secs(60);
+
+        cache.insert(key("a", "1"), 1usize, ttl);
+        cache.insert(key("a", "2"), 2usize, ttl);
+        // "1" is now used more recently than "2"
+        cache.get::<usize>(&key("a", "1"));
+        cache.insert(key("a", "3"), 3usize, ttl);
+
+        assert_eq!(cache.get::<usize>(&key("a", "1")), Some(1));
+        assert_eq!(cache.get::<usize>(&key("a", "2")), None);
+        assert_eq!(cache.get::<usize>(&key("a", "3")), Some(3));
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn invalidates_one_user() {
+        let cache = ResponseCache::new(CacheConfig::default());
+        let ttl = Duration::from_secs(60);
+
+        cache.insert(key("a", "1"), 1usize, ttl);
+        cache.insert(key("b", "1"), 1usize, ttl);
+        cache.invalidate_user("a");
+
+        assert_eq!(cache.get::<usize>(&key("a", "1")), None);
+        assert_eq!(cache.get::<usize>(&key("b", "1")), Some(1));
+    }
+
+    #[test]
+    fn mutations_are_never_cached() {
+        let mut config = CacheConfig::default();
+
+        assert!(config
+            .set_ttl(Rpc::RegisterWifiMac, Duration::from_secs(1))
+            .is_err());
+        assert_eq!(config.ttl(Rpc::FillFacultyFeedback), None);
+
+        config.disable(Rpc::GetAttendance);
+        assert_eq!(config.ttl(Rpc::GetAttendance), None);
+    }
+}
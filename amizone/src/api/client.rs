@@ -1,4 +1,5 @@
-use super::{types::*, user::credentials::UserMetaData};
+use super::{backend::AmizoneBackend, connection::Rpc, types::*, user::credentials::UserMetaData};
+use async_trait::async_trait;
 use go_amizone::server::proto::v1::{
     ClassScheduleRequest, DeregisterWifiMacRequest, EmptyMessage, FillFacultyFeedbackRequest,
     RegisterWifiMacRequest, SemesterRef,
@@ -26,129 +27,206 @@ impl UserClient {
             .insert("authorization", self.metadata.clone());
         request
     }
+}
 
-    pub async fn get_attendance(&mut self) -> Result<Vec<AttendanceRecord>> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_attendance(request).await?.into_inner();
-        drop(amizone);
+#[async_trait]
+impl AmizoneBackend for UserClient {
+    async fn get_attendance(&self) -> Result<Vec<AttendanceRecord>> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetAttendance,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_attendance(request).await },
+            )
+            .await?;
 
         Ok(response.records)
     }
 
-    pub async fn get_exam_schedule(&mut self) -> Result<(String, Vec<ScheduledExam>)> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_exam_schedule(request).await?.into_inner();
-        drop(amizone);
+    async fn get_exam_schedule(&self) -> Result<(String, Vec<ScheduledExam>)> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetExamSchedule,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_exam_schedule(request).await },
+            )
+            .await?;
 
         Ok((response.title, response.exams))
     }
 
-    pub async fn get_semesters(&mut self) -> Result<Vec<Semester>> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_semesters(request).await?.into_inner();
-        drop(amizone);
+    async fn get_semesters(&self) -> Result<Vec<Semester>> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetSemesters,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_semesters(request).await },
+            )
+            .await?;
 
         Ok(response.semesters)
     }
 
-    pub async fn get_current_courses(&mut self) -> Result<Vec<Course>> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_current_courses(request).await?.into_inner();
-        drop(amizone);
+    async fn get_current_courses(&self) -> Result<Vec<Course>> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetCurrentCourses,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_current_courses(request).await },
+            )
+            .await?;
 
         Ok(response.courses)
     }
 
-    pub async fn get_user_profile(&mut self) -> Result<AmizoneProfile> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_user_profile(request).await?.into_inner();
-        drop(amizone);
+    async fn get_user_profile(&self) -> Result<AmizoneProfile> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetUserProfile,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_user_profile(request).await },
+            )
+            .await?;
 
         Ok(response)
     }
 
-    pub async fn get_wifi_mac_info(&mut self) -> Result<WifiMacInfo> {
-        let request = self.prepare_request(EmptyMessage {});
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_wifi_mac_info(request).await?.into_inner();
-        drop(amizone);
+    async fn get_wifi_mac_info(&self) -> Result<WifiMacInfo> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetWifiMacInfo,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_wifi_mac_info(request).await },
+            )
+            .await?;
 
         Ok(response)
     }
 
-    pub async fn get_courses(&mut self, num: usize) -> Result<Vec<Course>> {
-        let request = self.prepare_request(SemesterRef {
-            semester_ref: num.to_string(),
-        });
-
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_courses(request).await?.into_inner();
-        drop(amizone);
+    async fn get_courses(&self, num: usize) -> Result<Vec<Course>> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetCourses,
+                || {
+                    self.prepare_request(SemesterRef {
+                        semester_ref: num.to_string(),
+                    })
+                },
+                |mut amizone, request| async move { amizone.get_courses(request).await },
+            )
+            .await?;
 
         Ok(response.courses)
     }
 
-    pub async fn register_wifi_mac(&mut self, addr: impl ToString) -> Result<()> {
-        let request = self.prepare_request(RegisterWifiMacRequest {
-            address: addr.to_string(),
-            override_limit: true,
-        });
+    async fn get_exam_result(&self, num: usize) -> Result<ExamResultRecords> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetExamResult,
+                || {
+                    self.prepare_request(SemesterRef {
+                        semester_ref: num.to_string(),
+                    })
+                },
+                |mut amizone, request| async move { amizone.get_exam_result(request).await },
+            )
+            .await?;
 
-        let mut amizone = self.connection.lock().await;
-        amizone.register_wifi_mac(request).await?;
-        drop(amizone);
+        Ok(response)
+    }
 
-        Ok(())
+    async fn get_current_exam_result(&self) -> Result<ExamResultRecords> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetCurrentExamResult,
+                || self.prepare_request(EmptyMessage {}),
+                |mut amizone, request| async move { amizone.get_current_exam_result(request).await },
+            )
+            .await?;
+
+        Ok(response)
     }
 
-    pub async fn deregister_wifi_mac(&mut self, addr: impl ToString) -> Result<()> {
-        let request = self.prepare_request(DeregisterWifiMacRequest {
-            address: addr.to_string(),
-        });
+    async fn register_wifi_mac(&self, addr: &str) -> Result<()> {
+        self.connection
+            .call(
+                Rpc::RegisterWifiMac,
+                || {
+                    self.prepare_request(RegisterWifiMacRequest {
+                        address: addr.to_string(),
+                        override_limit: true,
+                    })
+                },
+                |mut amizone, request| async move { amizone.register_wifi_mac(request).await },
+            )
+            .await?;
 
-        let mut amizone = self.connection.lock().await;
-        amizone.deregister_wifi_mac(request).await?;
-        drop(amizone);
+        Ok(())
+    }
+
+    async fn deregister_wifi_mac(&self, addr: &str) -> Result<()> {
+        self.connection
+            .call(
+                Rpc::DeregisterWifiMac,
+                || {
+                    self.prepare_request(DeregisterWifiMacRequest {
+                        address: addr.to_string(),
+                    })
+                },
+                |mut amizone, request| async move { amizone.deregister_wifi_mac(request).await },
+            )
+            .await?;
 
         Ok(())
     }
 
-    pub async fn fill_faculty_feedback(
-        &mut self,
+    async fn fill_faculty_feedback(
+        &self,
         rating: i32,
         query_rating: i32,
-        comment: impl ToString,
+        comment: &str,
     ) -> Result<i32> {
-        let request = self.prepare_request(FillFacultyFeedbackRequest {
-            rating,
-            query_rating,
-            comment: comment.to_string(),
-        });
-
-        let mut amizone = self.connection.lock().await;
-        let filled = amizone.fill_faculty_feedback(request).await?.into_inner();
-        drop(amizone);
+        let filled = self
+            .connection
+            .call(
+                Rpc::FillFacultyFeedback,
+                || {
+                    self.prepare_request(FillFacultyFeedbackRequest {
+                        rating,
+                        query_rating,
+                        comment: comment.to_string(),
+                    })
+                },
+                |mut amizone, request| async move { amizone.fill_faculty_feedback(request).await },
+            )
+            .await?;
 
         Ok(filled.filled_for)
     }
 
-    pub async fn get_class_schedule(&mut self, date: Date) -> Result<Vec<ScheduledClass>> {
-        let request = self.prepare_request(ClassScheduleRequest { date: Some(date) });
+    async fn get_class_schedule(&self, date: Date) -> Result<Vec<ScheduledClass>> {
+        let response = self
+            .connection
+            .call(
+                Rpc::GetClassSchedule,
+                || {
+                    self.prepare_request(ClassScheduleRequest {
+                        date: Some(date.clone()),
+                    })
+                },
+                |mut amizone, request| async move { amizone.get_class_schedule(request).await },
+            )
+            .await?;
 
-        let mut amizone = self.connection.lock().await;
-        let response = amizone.get_class_schedule(request).await?.into_inner();
-        drop(amizone);
         Ok(response.classes)
     }
 }
@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+use tonic::{Code, Request, Response, Status};
+use tower::discover::Change;
+
+use super::types::*;
+use crate::config::{env_var, parse_secs};
+use go_amizone::server::proto::v1::amizone_service_client::AmizoneServiceClient;
+
+/// A connection to go-amizone, cheap to clone and shared between every user.
+///
+/// Requests are multiplexed over HTTP/2, the underlying channel(s) connect lazily on the first
+/// request and reconnect on their own whenever the connection drops. Every call goes through
+/// the [`ConnectionPolicy`] for its deadline and retries.
+#[derive(Clone)]
+pub struct AmizoneConnection {
+    client: AmizoneServiceClient<Channel>,
+    policy: Arc<ConnectionPolicy>,
+}
+
+/// Opens a connection to go-amizone as described by `config`, without waiting for the backend
+/// to be reachable.
+pub fn new_amizone_connection(config: ConnectionConfig) -> Result<AmizoneConnection> {
+    let mut endpoint = Channel::from_shared(config.url.clone())
+        .map_err(|_| Status::internal("Invalid URL for amizone backend"))?
+        .connect_timeout(config.connect_timeout);
+
+    if let Some(timeout) = config.request_timeout {
+        endpoint = endpoint.timeout(timeout);
+    }
+
+    if let Some(interval) = config.keepalive {
+        endpoint = endpoint
+            .tcp_keepalive(Some(interval))
+            .http2_keep_alive_interval(interval)
+            .keep_alive_timeout(config.keepalive_timeout)
+            .keep_alive_while_idle(true);
+    }
+
+    // Plain http urls are served over h2c, for locally running go-amizone instances
+    if !config.is_plaintext() {
+        endpoint = endpoint
+            .tls_config(config.tls.client_config()?)
+            .map_err(|_| Status::internal("Invlaid TLS config"))?;
+    }
+
+    let channel = if config.pool_size > 1 {
+        // Keyed by index, as keying by uri (like `Channel::balance_list`) would collapse
+        // every endpoint into one
+        let (channel, endpoints) = Channel::balance_channel(config.pool_size);
+        for i in 0..config.pool_size {
+            endpoints
+                .try_send(Change::Insert(i, endpoint.clone()))
+                .map_err(|_| Status::internal("Couldn't set up amizone connection pool"))?;
+        }
+        channel
+    } else {
+        endpoint.connect_lazy()
+    };
+
+    Ok(AmizoneConnection {
+        client: AmizoneServiceClient::new(channel),
+        policy: Arc::new(config.policy),
+    })
+}
+
+/// Where and how to connect to go-amizone
+#[derive(Debug, Clone)]
+pub struct ConnectionConfig {
+    /// `https://` for TLS, `http://` for plaintext h2c (local development)
+    pub url: String,
+    /// With more than 1, that many channels are opened to the backend and requests are load
+    /// balanced across them
+    pub pool_size: usize,
+    /// Only used for `https://` urls
+    pub tls: TlsOptions,
+    /// Interval of TCP and HTTP/2 keepalive pings, none by default
+    pub keepalive: Option<Duration>,
+    /// How long to wait for a keepalive ping to be acknowledged before dropping the connection
+    pub keepalive_timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Upper bound on any request, on top of the per RPC deadlines of the policy
+    pub request_timeout: Option<Duration>,
+    pub policy: ConnectionPolicy,
+}
+
+impl ConnectionConfig {
+    pub fn new(url: impl ToString) -> Self {
+        Self {
+            url: url.to_string(),
+            pool_size: 1,
+            tls: TlsOptions::default(),
+            keepalive: None,
+            keepalive_timeout: Duration::from_secs(20),
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: None,
+            policy: ConnectionPolicy::default(),
+        }
+    }
+
+    /// Reads the config from the environment
+    ///
+    /// - `AMIZONE_API_URL`: defaults to `https://amizone.fly.dev`
+    /// - `AMIZONE_POOL_SIZE`: number of channels, defaults to 1
+    /// - `AMIZONE_TLS_ROOTS`: `system`, `webpki` or the path of a PEM file with the CA
+    ///   certificate(s), defaults to `./tls/lets-encrypt.pem`
+    /// - `AMIZONE_TLS_DOMAIN`: name to verify the server certificate against, defaults to
+    ///   the host of the url
+    /// - `AMIZONE_CLIENT_CERT` and `AMIZONE_CLIENT_KEY`: PEM files for client authentication
+    /// - `AMIZONE_KEEPALIVE`, `AMIZONE_KEEPALIVE_TIMEOUT`, `AMIZONE_CONNECT_TIMEOUT` and
+    ///   `AMIZONE_REQUEST_TIMEOUT`: in seconds
+    /// - and everything read by [`ConnectionPolicy::from_env`]
+    pub fn from_env() -> std::result::Result<Self, String> {
+        let mut config = Self::new(
+            env_var("AMIZONE_API_URL").unwrap_or_else(|| "https://amizone.fly.dev".to_string()),
+        );
+
+        if let Some(size) = env_var("AMIZONE_POOL_SIZE") {
+            config.pool_size = size
+                .parse()
+                .map_err(|_| format!("Invalid `AMIZONE_POOL_SIZE`: {}", size))?;
+        }
+
+        if let Some(roots) = env_var("AMIZONE_TLS_ROOTS") {
+            config.tls.roots = roots.parse()?;
+        }
+        config.tls.domain = env_var("AMIZONE_TLS_DOMAIN");
+        config.tls.identity = match (
+            env_var("AMIZONE_CLIENT_CERT"),
+            env_var("AMIZONE_CLIENT_KEY"),
+        ) {
+            (Some(cert), Some(key)) => Some((cert.into(), key.into())),
+            (None, None) => None,
+            _ => {
+                return Err(
+                    "`AMIZONE_CLIENT_CERT` and `AMIZONE_CLIENT_KEY` must be set together".into(),
+                )
+            }
+        };
+
+        if let Some(secs) = env_var("AMIZONE_KEEPALIVE") {
+            config.keepalive = Some(parse_secs(&secs)?);
+        }
+        if let Some(secs) = env_var("AMIZONE_KEEPALIVE_TIMEOUT") {
+            config.keepalive_timeout = parse_secs(&secs)?;
+        }
+        if let Some(secs) = env_var("AMIZONE_CONNECT_TIMEOUT") {
+            config.connect_timeout = parse_secs(&secs)?;
+        }
+        if let Some(secs) = env_var("AMIZONE_REQUEST_TIMEOUT") {
+            config.request_timeout = Some(parse_secs(&secs)?);
+        }
+
+        config.policy = ConnectionPolicy::from_env()?;
+
+        Ok(config)
+    }
+
+    pub fn is_plaintext(&self) -> bool {
+        self.url.starts_with("http://")
+    }
+}
+
+/// How the server certificate is verified, and the client's own certificate if any
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    pub roots: TlsRoots,
+    /// Overrides the name the server certificate is verified against (SNI), which is the host
+    /// of the url by default
+    pub domain: Option<String>,
+    /// (certificate, private key) PEM files
+    pub identity: Option<(PathBuf, PathBuf)>,
+}
+
+impl TlsOptions {
+    fn client_config(&self) -> Result<ClientTlsConfig> {
+        let mut config = ClientTlsConfig::new();
+
+        match self.roots {
+            TlsRoots::CaFile(ref path) => {
+                let pem = std::fs::read_to_string(path).map_err(|err| {
+                    Status::internal(format!(
+                        "Error reading TLS cert {}: {}",
+                        path.display(),
+                        err
+                    ))
+                })?;
+                config = config.ca_certificate(Certificate::from_pem(pem));
+            }
+            // The built in roots are compiled into tonic, and are always trusted once they are
+            TlsRoots::System if !cfg!(feature = "tls-roots") => {
+                return Err(Status::internal(
+                    "System TLS roots need amizone to be built with the `tls-roots` feature",
+                ))
+            }
+            TlsRoots::WebPki if !cfg!(feature = "tls-webpki-roots") => {
+                return Err(Status::internal(
+                    "webpki TLS roots need amizone to be built with the `tls-webpki-roots` feature",
+                ))
+            }
+            TlsRoots::System | TlsRoots::WebPki => {}
+        }
+
+        if let Some(ref domain) = self.domain {
+            config = config.domain_name(domain);
+        }
+
+        if let Some((ref cert, ref key)) = self.identity {
+            let read = |path: &PathBuf| {
+                std::fs::read(path).map_err(|err| {
+                    Status::internal(format!("Error reading {}: {}", path.display(), err))
+                })
+            };
+            config = config.identity(Identity::from_pem(read(cert)?, read(key)?));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Certificate authorities trusted to sign the server certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsRoots {
+    /// PEM file with the CA certificate(s)
+    CaFile(PathBuf),
+    /// The OS certificate store, needs the `tls-roots` feature
+    System,
+    /// Mozilla's roots bundled with webpki, needs the `tls-webpki-roots` feature
+    WebPki,
+}
+
+impl Default for TlsRoots {
+    fn default() -> Self {
+        TlsRoots::CaFile(PathBuf::from("./tls/lets-encrypt.pem"))
+    }
+}
+
+impl FromStr for TlsRoots {
+    type Err = String;
+
+    /// `system`, `webpki` or else a path to a PEM file
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "" => return Err("Empty TLS roots".into()),
+            "system" => TlsRoots::System,
+            "webpki" => TlsRoots::WebPki,
+            path => TlsRoots::CaFile(PathBuf::from(path)),
+        })
+    }
+}
+
+impl AmizoneConnection {
+    /// Sends the request built by `prepare` with `send`, under the deadline for `rpc`.
+    ///
+    /// Idempotent RPCs are retried with backoff on transient failures, with a fresh request
+    /// from `prepare` every attempt.
+    pub(crate) async fn call<M, R, Fut>(
+        &self,
+        rpc: Rpc,
+        prepare: impl Fn() -> Request<M>,
+        send: impl Fn(AmizoneServiceClient<Channel>, Request<M>) -> Fut,
+    ) -> Result<R>
+    where
+        Fut: Future<Output = Result<Response<R>>>,
+    {
+        let deadline = self.policy.deadlines.get(rpc);
+        let mut attempt = 0;
+
+        loop {
+            let mut request = prepare();
+            request.set_timeout(deadline);
+
+            let result =
+                match tokio::time::timeout(deadline, send(self.client.clone(), request)).await {
+                    Ok(result) => result.map(Response::into_inner),
+                    Err(_) => Err(Status::deadline_exceeded(format!(
+                        "{:?} took longer than {:?}",
+                        rpc, deadline
+                    ))),
+                };
+
+            match result {
+                Err(status)
+                    if rpc.is_idempotent()
+                        && attempt < self.policy.retry.max_retries
+                        && RetryPolicy::is_transient(&status) =>
+                {
+                    tokio::time::sleep(self.policy.retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Every RPC of the go-amizone service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rpc {
+    GetAttendance,
+    GetClassSchedule,
+    GetExamSchedule,
+    GetSemesters,
+    GetCourses,
+    GetCurrentCourses,
+    GetExamResult,
+    GetCurrentExamResult,
+    GetUserProfile,
+    GetWifiMacInfo,
+    RegisterWifiMac,
+    DeregisterWifiMac,
+    FillFacultyFeedback,
+}
+
+impl Rpc {
+    /// Whether sending the RPC twice is harmless, only these are ever retried
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(
+            self,
+            Rpc::RegisterWifiMac | Rpc::DeregisterWifiMac | Rpc::FillFacultyFeedback
+        )
+    }
+}
+
+impl FromStr for Rpc {
+    type Err = String;
+
+    /// Parses the RPC name as it appears in the proto, eg. `GetAttendance`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "GetAttendance" => Rpc::GetAttendance,
+            "GetClassSchedule" => Rpc::GetClassSchedule,
+            "GetExamSchedule" => Rpc::GetExamSchedule,
+            "GetSemesters" => Rpc::GetSemesters,
+            "GetCourses" => Rpc::GetCourses,
+            "GetCurrentCourses" => Rpc::GetCurrentCourses,
+            "GetExamResult" => Rpc::GetExamResult,
+            "GetCurrentExamResult" => Rpc::GetCurrentExamResult,
+            "GetUserProfile" => Rpc::GetUserProfile,
+            "GetWifiMacInfo" => Rpc::GetWifiMacInfo,
+            "RegisterWifiMac" => Rpc::RegisterWifiMac,
+            "DeregisterWifiMac" => Rpc::DeregisterWifiMac,
+            "FillFacultyFeedback" => Rpc::FillFacultyFeedback,
+            _ => return Err(format!("Unknown RPC `{}`", s)),
+        })
+    }
+}
+
+/// How calls made through an [`AmizoneConnection`] are retried and timed out
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionPolicy {
+    pub retry: RetryPolicy,
+    pub deadlines: Deadlines,
+}
+
+impl ConnectionPolicy {
+    /// Defaults, overridden by the following when they are set and not blank
+    /// - `AMIZONE_MAX_RETRIES`: retries for idempotent calls
+    /// - `AMIZONE_DEADLINE`: deadline in seconds for every call
+    /// - `AMIZONE_DEADLINES`: per RPC deadlines in seconds, eg. `GetClassSchedule=20,FillFacultyFeedback=120`
+    pub fn from_env() -> std::result::Result<Self, String> {
+        let mut policy = Self::default();
+
+        if let Some(retries) = env_var("AMIZONE_MAX_RETRIES") {
+            policy.retry.max_retries = retries
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid `AMIZONE_MAX_RETRIES`: {}", retries))?;
+        }
+
+        if let Some(deadline) = env_var("AMIZONE_DEADLINE") {
+            policy.deadlines.default = parse_secs(&deadline)?;
+        }
+
+        if let Some(deadlines) = env_var("AMIZONE_DEADLINES") {
+            for entry in deadlines
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+            {
+                let (rpc, secs) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected `Rpc=seconds`, got `{}`", entry))?;
+                policy.deadlines.set(rpc.trim().parse()?, parse_secs(secs)?);
+            }
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Exponential backoff with full jitter between retries
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retry number `attempt` (starting at 0), a random duration
+    /// up to `base_delay * 2^attempt`, capped at `max_delay`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        rand::thread_rng().gen_range(Duration::ZERO..=ceiling)
+    }
+
+    /// Failures that might go away on their own, the backend being unreachable or slow
+    pub fn is_transient(status: &Status) -> bool {
+        matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded)
+    }
+}
+
+/// Deadline for every call, with overrides for individual RPCs
+#[derive(Debug, Clone)]
+pub struct Deadlines {
+    pub default: Duration,
+    overrides: HashMap<Rpc, Duration>,
+}
+
+impl Default for Deadlines {
+    fn default() -> Self {
+        Self {
+            default: Duration::from_secs(15),
+            // Fills the form for every faculty one by one
+            overrides: HashMap::from([(Rpc::FillFacultyFeedback, Duration::from_secs(60))]),
+        }
+    }
+}
+
+impl Deadlines {
+    pub fn get(&self, rpc: Rpc) -> Duration {
+        self.overrides.get(&rpc).copied().unwrap_or(self.default)
+    }
+
+    pub fn set(&mut self, rpc: Rpc, deadline: Duration) {
+        self.overrides.insert(rpc, deadline);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+
+        for attempt in 0..10 {
+            let ceiling = (Duration::from_millis(100) * 2u32.pow(attempt)).min(policy.max_delay);
+            assert!(policy.backoff(attempt) <= ceiling);
+        }
+    }
+
+    #[test]
+    fn only_reads_are_idempotent() {
+        assert!(Rpc::GetAttendance.is_idempotent());
+        assert!(Rpc::GetClassSchedule.is_idempotent());
+        assert!(!Rpc::RegisterWifiMac.is_idempotent());
+        assert!(!Rpc::DeregisterWifiMac.is_idempotent());
+        assert!(!Rpc::FillFacultyFeedback.is_idempotent());
+    }
+
+    #[test]
+    fn deadline_overrides() {
+        let mut deadlines = Deadlines::default();
+        deadlines.set("GetClassSchedule".parse().unwrap(), Duration::from_secs(20));
+
+        assert_eq!(
+            deadlines.get(Rpc::GetClassSchedule),
+            Duration::from_secs(20)
+        );
+        assert_eq!(deadlines.get(Rpc::GetAttendance), deadlines.default);
+        assert!("GetNothing".parse::<Rpc>().is_err());
+    }
+
+    #[test]
+    fn tls_roots() {
+        assert_eq!("system".parse(), Ok(TlsRoots::System));
+        assert_eq!("webpki".parse(), Ok(TlsRoots::WebPki));
+        assert_eq!(
+            "/etc/amibot/ca.pem".parse(),
+            Ok(TlsRoots::CaFile(PathBuf::from("/etc/amibot/ca.pem")))
+        );
+        assert!(" ".parse::<TlsRoots>().is_err());
+    }
+
+    #[test]
+    fn plaintext_urls() {
+        assert!(ConnectionConfig::new("http://localhost:8081").is_plaintext());
+        assert!(!ConnectionConfig::new("https://amizone.fly.dev").is_plaintext());
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+use crate::api::types::{DatabaseConnection, DbOperationResult};
+
+/// A collection of documents of one type, stored as JSON in a [`DocumentStore`](super::DocumentStore)
+pub struct Documents<T> {
+    store: DatabaseConnection,
+    collection: &'static str,
+    document: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Documents<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            collection: self.collection,
+            document: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Documents<T> {
+    pub fn new(store: DatabaseConnection, collection: &'static str) -> Self {
+        Self {
+            store,
+            collection,
+            document: PhantomData,
+        }
+    }
+
+    pub fn collection(&self) -> &'static str {
+        self.collection
+    }
+
+    pub async fn get(&self, key: &str) -> DbOperationResult<Option<T>> {
+        self.store
+            .get_document(self.collection, key)
+            .await?
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    pub async fn put(&self, key: &str, document: &T) -> DbOperationResult<()> {
+        self.store
+            .put_document(self.collection, key, serde_json::to_string(document)?)
+            .await
+    }
+
+    pub async fn remove(&self, key: &str) -> DbOperationResult<Option<T>> {
+        self.store
+            .remove_document(self.collection, key)
+            .await?
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Every document with its key, documents that don't parse (eg. written by an older
+    /// version with a different shape) are skipped
+    pub async fn list(&self) -> DbOperationResult<Vec<(String, T)>> {
+        Ok(self
+            .store
+            .list_documents(self.collection)
+            .await?
+            .into_iter()
+            .filter_map(|(key, value)| Some((key, serde_json::from_str(&value).ok()?)))
+            .collect())
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use super::{CredentialStore, DocumentStore, UserRecord};
+use crate::api::types::DbOperationResult;
+
+/// Keeps everything in a map, lost when the process exits.
+#[derive(Default)]
+pub struct MemoryStore {
+    users: RwLock<HashMap<String, UserRecord>>,
+    documents: RwLock<HashMap<String, BTreeMap<String, String>>>,
+}
+
+#[async_trait]
+impl CredentialStore for MemoryStore {
+    async fn get_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self.users.read().unwrap().get(id).cloned())
+    }
+
+    async fn insert_user(&self, record: UserRecord) -> DbOperationResult<()> {
+        self.users
+            .write()
+            .unwrap()
+            .entry(record.id.clone())
+            .or_insert(record);
+        Ok(())
+    }
+
+    async fn update_user(
+        &self,
+        id: &str,
+        metadata: String,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self.users.write().unwrap().get_mut(id).map(|record| {
+            record.metadata = metadata;
+            record.clone()
+        }))
+    }
+
+    async fn remove_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self.users.write().unwrap().remove(id))
+    }
+
+    async fn list_users(&self) -> DbOperationResult<Vec<UserRecord>> {
+        Ok(self.users.read().unwrap().values().cloned().collect())
+    }
+
+    async fn set_feed_token(
+        &self,
+        id: &str,
+        token: Option<String>,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self.users.write().unwrap().get_mut(id).map(|record| {
+            record.feed_token = token;
+            record.clone()
+        }))
+    }
+
+    async fn get_user_by_feed_token(&self, token: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self
+            .users
+            .read()
+            .unwrap()
+            .values()
+            .find(|record| record.feed_token.as_deref() == Some(token))
+            .cloned())
+    }
+}
+
+#[async_trait]
+impl DocumentStore for MemoryStore {
+    async fn get_document(&self, collection: &str, key: &str) -> DbOperationResult<Option<String>> {
+        Ok(self
+            .documents
+            .read()
+            .unwrap()
+            .get(collection)
+            .and_then(|documents| documents.get(key))
+            .cloned())
+    }
+
+    async fn put_document(
+        &self,
+        collection: &str,
+        key: &str,
+        value: String,
+    ) -> DbOperationResult<()> {
+        self.documents
+            .write()
+            .unwrap()
+            .entry(collection.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn remove_document(
+        &self,
+        collection: &str,
+        key: &str,
+    ) -> DbOperationResult<Option<String>> {
+        Ok(self
+            .documents
+            .write()
+            .unwrap()
+            .get_mut(collection)
+            .and_then(|documents| documents.remove(key)))
+    }
+
+    async fn list_documents(&self, collection: &str) -> DbOperationResult<Vec<(String, String)>> {
+        Ok(self
+            .documents
+            .read()
+            .unwrap()
+            .get(collection)
+            .map(|documents| {
+                documents
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
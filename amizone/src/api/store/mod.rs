@@ -0,0 +1,164 @@
+//! Where users (and their encrypted credentials) are persisted.
+//!
+//! [`new_db_connection`](super::new_db_connection) picks the backend from the scheme of the
+//! database url:
+//! - `mongodb://` or `mongodb+srv://`: [`MongoStore`]
+//! - `sqlite://<path>` or `sqlite::memory:`: [`SqliteStore`], for small self-hosted deployments
+//! - `memory://`: [`MemoryStore`], nothing survives a restart, meant for tests and demos
+
+mod documents;
+mod memory;
+mod mongo;
+mod sqlite;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub use documents::Documents;
+pub use memory::MemoryStore;
+pub use mongo::MongoStore;
+pub use sqlite::SqliteStore;
+
+use super::types::{DatabaseConnection, DbOperationResult};
+use super::user::credentials::CredentialError;
+
+/// A user as it is stored, the credentials stay encrypted until
+/// [`User`](super::user::User) decrypts them.
+///
+/// Serializes to the same document shape the MongoDB collection has always used.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserRecord {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub metadata: String,
+    /// Secret in the url of the user's calendar feed, if they have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed_token: Option<String>,
+}
+
+#[async_trait]
+pub trait CredentialStore: Send + Sync {
+    async fn get_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>>;
+
+    async fn insert_user(&self, record: UserRecord) -> DbOperationResult<()>;
+
+    /// Replaces the metadata of an existing user, returns the updated record
+    async fn update_user(
+        &self,
+        id: &str,
+        metadata: String,
+    ) -> DbOperationResult<Option<UserRecord>>;
+
+    /// Returns the removed record, if there was one
+    async fn remove_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>>;
+
+    async fn list_users(&self) -> DbOperationResult<Vec<UserRecord>>;
+
+    /// Replaces (or clears, with `None`) the feed token of an existing user, returns the
+    /// updated record
+    async fn set_feed_token(
+        &self,
+        id: &str,
+        token: Option<String>,
+    ) -> DbOperationResult<Option<UserRecord>>;
+
+    async fn get_user_by_feed_token(&self, token: &str) -> DbOperationResult<Option<UserRecord>>;
+}
+
+/// Small documents of the bot's own (jobs, settings, snapshots) kept next to the users. Values
+/// are opaque strings grouped in named collections, [`Documents`] stores typed values as JSON.
+#[async_trait]
+pub trait DocumentStore: Send + Sync {
+    async fn get_document(&self, collection: &str, key: &str) -> DbOperationResult<Option<String>>;
+
+    /// Inserts or replaces the document at `key`
+    async fn put_document(
+        &self,
+        collection: &str,
+        key: &str,
+        value: String,
+    ) -> DbOperationResult<()>;
+
+    /// Returns the removed document, if there was one
+    async fn remove_document(
+        &self,
+        collection: &str,
+        key: &str,
+    ) -> DbOperationResult<Option<String>>;
+
+    /// Every document in the collection, with its key
+    async fn list_documents(&self, collection: &str) -> DbOperationResult<Vec<(String, String)>>;
+}
+
+/// Everything a database backend stores, what [`DatabaseConnection`] points to
+pub trait Store: CredentialStore + DocumentStore {}
+
+impl<T: CredentialStore + DocumentStore + ?Sized> Store for T {}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum StoreError {
+    Mongo(mongodb::error::Error),
+    Sqlite(Arc<rusqlite::Error>),
+    Credentials(CredentialError),
+    /// A document couldn't be turned into (or from) JSON
+    Json(Arc<serde_json::Error>),
+    /// The database url doesn't name a supported backend
+    InvalidUrl(String),
+    Internal(String),
+}
+
+impl From<mongodb::error::Error> for StoreError {
+    fn from(value: mongodb::error::Error) -> Self {
+        StoreError::Mongo(value)
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(value: rusqlite::Error) -> Self {
+        StoreError::Sqlite(Arc::new(value))
+    }
+}
+
+impl From<CredentialError> for StoreError {
+    fn from(value: CredentialError) -> Self {
+        StoreError::Credentials(value)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(value: serde_json::Error) -> Self {
+        StoreError::Json(Arc::new(value))
+    }
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Mongo(err) => write!(f, "MongoDB error: {}", err),
+            StoreError::Sqlite(err) => write!(f, "SQLite error: {}", err),
+            StoreError::Credentials(err) => write!(f, "Unreadable credentials: {}", err),
+            StoreError::Json(err) => write!(f, "Malformed document: {}", err),
+            StoreError::InvalidUrl(url) => write!(f, "Unsupported database url: {}", url),
+            StoreError::Internal(err) => write!(f, "Internal store error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Opens the store named by `addr`, see the [module docs](self) for the supported schemes.
+pub async fn connect(addr: &str) -> DbOperationResult<DatabaseConnection> {
+    if addr.starts_with("mongodb://") || addr.starts_with("mongodb+srv://") {
+        Ok(Arc::new(MongoStore::connect(addr).await?))
+    } else if let Some(path) = addr.strip_prefix("sqlite://") {
+        Ok(Arc::new(SqliteStore::open(path).await?))
+    } else if addr == "sqlite::memory:" {
+        Ok(Arc::new(SqliteStore::open(":memory:").await?))
+    } else if addr.starts_with("memory:") {
+        Ok(Arc::new(MemoryStore::default()))
+    } else {
+        Err(StoreError::InvalidUrl(addr.to_string()))
+    }
+}
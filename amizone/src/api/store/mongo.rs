@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{
+        ClientOptions, FindOneAndUpdateOptions, ReplaceOptions, ReturnDocument, ServerApi,
+        ServerApiVersion,
+    },
+    Client, Collection,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CredentialStore, DocumentStore, UserRecord};
+use crate::api::types::DbOperationResult;
+
+static DATABASE_NAME: &str = "amibot_users";
+static COLLECTION_NAME: &str = "login_credentials";
+static DOCUMENTS_COLLECTION_NAME: &str = "documents";
+
+/// Documents of every collection share one MongoDB collection, keyed by both names
+#[derive(Serialize, Deserialize)]
+struct DocumentRecord {
+    #[serde(rename = "_id")]
+    id: String,
+    collection: String,
+    key: String,
+    value: String,
+}
+
+fn document_id(collection: &str, key: &str) -> String {
+    format!("{}/{}", collection, key)
+}
+
+pub struct MongoStore {
+    client: Client,
+}
+
+impl MongoStore {
+    pub async fn connect(addr: &str) -> DbOperationResult<Self> {
+        let mut client_options = ClientOptions::parse(addr).await?;
+
+        let server_api = ServerApi::builder().version(ServerApiVersion::V1).build();
+        client_options.server_api = Some(server_api);
+
+        let client = Client::with_options(client_options)?;
+
+        Ok(Self { client })
+    }
+
+    fn users(&self) -> Collection<UserRecord> {
+        self.client
+            .database(DATABASE_NAME)
+            .collection::<UserRecord>(COLLECTION_NAME)
+    }
+
+    fn documents(&self) -> Collection<DocumentRecord> {
+        self.client
+            .database(DATABASE_NAME)
+            .collection::<DocumentRecord>(DOCUMENTS_COLLECTION_NAME)
+    }
+}
+
+#[async_trait]
+impl CredentialStore for MongoStore {
+    async fn get_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self.users().find_one(doc! { "_id": id }, None).await?)
+    }
+
+    async fn insert_user(&self, record: UserRecord) -> DbOperationResult<()> {
+        self.users().insert_one(record, None).await?;
+        Ok(())
+    }
+
+    async fn update_user(
+        &self,
+        id: &str,
+        metadata: String,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        Ok(self
+            .users()
+            .find_one_and_update(
+                doc! { "_id": id },
+                doc! { "$set": { "metadata": metadata } },
+                options,
+            )
+            .await?)
+    }
+
+    async fn remove_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self
+            .users()
+            .find_one_and_delete(doc! { "_id": id }, None)
+            .await?)
+    }
+
+    async fn list_users(&self) -> DbOperationResult<Vec<UserRecord>> {
+        Ok(self
+            .users()
+            .find(None, None)
+            .await?
+            .try_collect::<Vec<UserRecord>>()
+            .await?)
+    }
+
+    async fn set_feed_token(
+        &self,
+        id: &str,
+        token: Option<String>,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let update = match token {
+            Some(token) => doc! { "$set": { "feed_token": token } },
+            None => doc! { "$unset": { "feed_token": "" } },
+        };
+
+        Ok(self
+            .users()
+            .find_one_and_update(doc! { "_id": id }, update, options)
+            .await?)
+    }
+
+    async fn get_user_by_feed_token(&self, token: &str) -> DbOperationResult<Option<UserRecord>> {
+        Ok(self
+            .users()
+            .find_one(doc! { "feed_token": token }, None)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl DocumentStore for MongoStore {
+    async fn get_document(&self, collection: &str, key: &str) -> DbOperationResult<Option<String>> {
+        Ok(self
+            .documents()
+            .find_one(doc! { "_id": document_id(collection, key) }, None)
+            .await?
+            .map(|record| record.value))
+    }
+
+    async fn put_document(
+        &self,
+        collection: &str,
+        key: &str,
+        value: String,
+    ) -> DbOperationResult<()> {
+        let record = DocumentRecord {
+            id: document_id(collection, key),
+            collection: collection.to_string(),
+            key: key.to_string(),
+            value,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.documents()
+            .replace_one(doc! { "_id": record.id.clone() }, &record, options)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_document(
+        &self,
+        collection: &str,
+        key: &str,
+    ) -> DbOperationResult<Option<String>> {
+        Ok(self
+            .documents()
+            .find_one_and_delete(doc! { "_id": document_id(collection, key) }, None)
+            .await?
+            .map(|record| record.value))
+    }
+
+    async fn list_documents(&self, collection: &str) -> DbOperationResult<Vec<(String, String)>> {
+        Ok(self
+            .documents()
+            .find(doc! { "collection": collection }, None)
+            .await?
+            .map_ok(|record| (record.key, record.value))
+            .try_collect::<Vec<(String, String)>>()
+            .await?)
+    }
+}
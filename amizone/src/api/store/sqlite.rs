@@ -0,0 +1,263 @@
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use super::{CredentialStore, DocumentStore, StoreError, UserRecord};
+use crate::api::types::DbOperationResult;
+
+/// An embedded SQLite database in a single file, the connection is shared behind a mutex
+/// and every query runs on tokio's blocking pool.
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens (or creates) the database at `path`, `:memory:` gives a private in-memory database.
+    pub async fn open(path: &str) -> DbOperationResult<Self> {
+        let path = path.to_string();
+        let connection = tokio::task::spawn_blocking(move || -> rusqlite::Result<Connection> {
+            let connection = Connection::open(path)?;
+            connection.execute_batch(
+                "CREATE TABLE IF NOT EXISTS users (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    metadata TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS documents (
+                    collection TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    value TEXT NOT NULL,
+                    PRIMARY KEY (collection, key)
+                );",
+            )?;
+            migrate(&connection)?;
+            Ok(connection)
+        })
+        .await
+        .map_err(|err| StoreError::Internal(err.to_string()))??;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn run<T, F>(&self, query: F) -> DbOperationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            query(&mut connection)
+        })
+        .await
+        .map_err(|err| StoreError::Internal(err.to_string()))?;
+
+        Ok(result?)
+    }
+}
+
+/// Brings a database made by an older version up to date, columns added since the table was
+/// first created are added here.
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    let has_feed_token = connection
+        .prepare("SELECT 1 FROM pragma_table_info('users') WHERE name = 'feed_token'")?
+        .exists([])?;
+
+    if !has_feed_token {
+        connection.execute_batch(
+            "ALTER TABLE users ADD COLUMN feed_token TEXT;
+            CREATE UNIQUE INDEX IF NOT EXISTS users_feed_token ON users (feed_token);",
+        )?;
+    }
+
+    Ok(())
+}
+
+fn to_record(row: &rusqlite::Row) -> rusqlite::Result<UserRecord> {
+    Ok(UserRecord {
+        id: row.get(0)?,
+        metadata: row.get(1)?,
+        feed_token: row.get(2)?,
+    })
+}
+
+fn select_user(connection: &Connection, id: &str) -> rusqlite::Result<Option<UserRecord>> {
+    connection
+        .query_row(
+            "SELECT id, metadata, feed_token FROM users WHERE id = ?1",
+            params![id],
+            to_record,
+        )
+        .optional()
+}
+
+#[async_trait]
+impl CredentialStore for SqliteStore {
+    async fn get_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        let id = id.to_string();
+        self.run(move |connection| select_user(connection, &id))
+            .await
+    }
+
+    async fn insert_user(&self, record: UserRecord) -> DbOperationResult<()> {
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO users (id, metadata, feed_token) VALUES (?1, ?2, ?3)",
+                params![record.id, record.metadata, record.feed_token],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_user(
+        &self,
+        id: &str,
+        metadata: String,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        let id = id.to_string();
+        self.run(move |connection| {
+            connection.execute(
+                "UPDATE users SET metadata = ?2 WHERE id = ?1",
+                params![id, metadata],
+            )?;
+
+            select_user(connection, &id)
+        })
+        .await
+    }
+
+    async fn remove_user(&self, id: &str) -> DbOperationResult<Option<UserRecord>> {
+        let id = id.to_string();
+        self.run(move |connection| {
+            let transaction = connection.transaction()?;
+            let record = select_user(&transaction, &id)?;
+            transaction.execute("DELETE FROM users WHERE id = ?1", params![id])?;
+            transaction.commit()?;
+
+            Ok(record)
+        })
+        .await
+    }
+
+    async fn list_users(&self) -> DbOperationResult<Vec<UserRecord>> {
+        self.run(|connection| {
+            let mut statement = connection.prepare("SELECT id, metadata, feed_token FROM users")?;
+            let records = statement
+                .query_map([], to_record)?
+                .collect::<rusqlite::Result<Vec<UserRecord>>>()?;
+
+            Ok(records)
+        })
+        .await
+    }
+
+    async fn set_feed_token(
+        &self,
+        id: &str,
+        token: Option<String>,
+    ) -> DbOperationResult<Option<UserRecord>> {
+        let id = id.to_string();
+        self.run(move |connection| {
+            connection.execute(
+                "UPDATE users SET feed_token = ?2 WHERE id = ?1",
+                params![id, token],
+            )?;
+
+            select_user(connection, &id)
+        })
+        .await
+    }
+
+    async fn get_user_by_feed_token(&self, token: &str) -> DbOperationResult<Option<UserRecord>> {
+        let token = token.to_string();
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT id, metadata, feed_token FROM users WHERE feed_token = ?1",
+                    params![token],
+                    to_record,
+                )
+                .optional()
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl DocumentStore for SqliteStore {
+    async fn get_document(&self, collection: &str, key: &str) -> DbOperationResult<Option<String>> {
+        let (collection, key) = (collection.to_string(), key.to_string());
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT value FROM documents WHERE collection = ?1 AND key = ?2",
+                    params![collection, key],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+        .await
+    }
+
+    async fn put_document(
+        &self,
+        collection: &str,
+        key: &str,
+        value: String,
+    ) -> DbOperationResult<()> {
+        let (collection, key) = (collection.to_string(), key.to_string());
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO documents (collection, key, value) VALUES (?1, ?2, ?3)",
+                params![collection, key, value],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove_document(
+        &self,
+        collection: &str,
+        key: &str,
+    ) -> DbOperationResult<Option<String>> {
+        let (collection, key) = (collection.to_string(), key.to_string());
+        self.run(move |connection| {
+            let transaction = connection.transaction()?;
+            let value = transaction
+                .query_row(
+                    "SELECT value FROM documents WHERE collection = ?1 AND key = ?2",
+                    params![collection, key],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            transaction.execute(
+                "DELETE FROM documents WHERE collection = ?1 AND key = ?2",
+                params![collection, key],
+            )?;
+            transaction.commit()?;
+
+            Ok(value)
+        })
+        .await
+    }
+
+    async fn list_documents(&self, collection: &str) -> DbOperationResult<Vec<(String, String)>> {
+        let collection = collection.to_string();
+        self.run(move |connection| {
+            let mut statement =
+                connection.prepare("SELECT key, value FROM documents WHERE collection = ?1")?;
+            let documents = statement
+                .query_map(params![collection], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+
+            Ok(documents)
+        })
+        .await
+    }
+}
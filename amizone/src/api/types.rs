@@ -1,7 +1,5 @@
 include!(concat!(env!("OUT_DIR"), "/_includes.rs"));
-use go_amizone::server::proto::v1::{
-    self as goamizone, amizone_service_client::AmizoneServiceClient,
-};
+use go_amizone::server::proto::v1 as goamizone;
 
 pub type Date = google::r#type::Date;
 pub type AttendanceRecord = goamizone::AttendanceRecord;
@@ -12,13 +10,16 @@ pub type Semester = goamizone::Semester;
 pub type Course = goamizone::Course;
 pub type AttendanceState = goamizone::AttendanceState;
 pub type WifiMacInfo = goamizone::WifiMacInfo;
-pub type AmizoneConnection =
-    std::sync::Arc<tokio::sync::Mutex<AmizoneServiceClient<tonic::transport::channel::Channel>>>;
-pub type DatabaseConnection = mongodb::Client;
+pub type ExamResultRecords = goamizone::ExamResultRecords;
+pub type ExamResultRecord = goamizone::ExamResultRecord;
+pub type OverallResult = goamizone::OverallResult;
+pub type AmizoneConnection = super::connection::AmizoneConnection;
+pub type AmizoneClient = std::sync::Arc<dyn super::backend::AmizoneBackend>;
+pub type DatabaseConnection = std::sync::Arc<dyn super::store::Store>;
 pub type AmizoneApiError = tonic::Status;
 pub type Result<T> = std::result::Result<T, AmizoneApiError>;
 pub type StatusCode = tonic::Code;
-pub type DbError = mongodb::error::Error;
+pub type DbError = super::store::StoreError;
 pub type DbOperationResult<T> = std::result::Result<T, DbError>;
 
 impl From<i32> for AttendanceState {
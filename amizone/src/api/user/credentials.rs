@@ -5,8 +5,11 @@ use base64::{
 use mongodb::bson::{doc, Bson};
 use once_cell::sync::Lazy;
 use serde::ser::{Serialize, SerializeStruct};
+use std::collections::HashMap;
 use tonic::metadata::AsciiMetadataValue;
 
+use crate::config::env_var;
+
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
     Aes256Gcm, Nonce,
@@ -16,11 +19,14 @@ use rand::RngCore;
 
 pub type UserMetaData = AsciiMetadataValue;
 
-static PRIVATE_KEY: Lazy<Vec<u8>> = Lazy::new(|| {
-    STANDARD
-        .decode(std::env::var("PRIVATE_ENCRYPTION_KEY").unwrap())
-        .unwrap()
-});
+static KEYRING: Lazy<Result<Keyring, CredentialError>> = Lazy::new(Keyring::from_env);
+
+/// Id `PRIVATE_ENCRYPTION_KEY` is known by, metadata in the legacy (unversioned) format
+/// is always decrypted with it.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Versioned metadata starts with this, followed by the key id and a `.`
+const METADATA_V1_PREFIX: &str = "v1.";
 
 ///The recommendation to use a 12-byte nonce for AES-GCM encryption comes from
 /// the National Institute of Standards and Technology (NIST)
@@ -32,6 +38,121 @@ const NONCE_SIZE: usize = 12;
 /// The nonce length when it is encoded to base 64
 const NONCE_LENGTH: usize = ((NONCE_SIZE + 2) / 3) * 4;
 
+/// All the keys credentials can be decrypted with, and the one they are encrypted with.
+struct Keyring {
+    active: String,
+    keys: HashMap<String, Vec<u8>>,
+}
+
+impl Keyring {
+    /// `PRIVATE_ENCRYPTION_KEY` is registered as [`DEFAULT_KEY_ID`], `ENCRYPTION_KEYS` adds more
+    /// as a comma separated list of `id:base64 key`, and `ACTIVE_ENCRYPTION_KEY_ID` picks the one
+    /// new metadata gets encrypted with (defaults to [`DEFAULT_KEY_ID`]). Variables left blank
+    /// count as unset.
+    fn from_env() -> Result<Self, CredentialError> {
+        let mut keys = HashMap::new();
+
+        if let Some(key) = env_var("PRIVATE_ENCRYPTION_KEY") {
+            keys.insert(
+                DEFAULT_KEY_ID.to_string(),
+                Self::decode_key(DEFAULT_KEY_ID, &key)?,
+            );
+        }
+
+        if let Some(list) = env_var("ENCRYPTION_KEYS") {
+            for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let (id, key) = entry
+                    .split_once(':')
+                    .filter(|(id, _)| !id.contains('.'))
+                    .ok_or_else(|| CredentialError::InvalidKey(entry.to_string()))?;
+
+                keys.insert(id.to_string(), Self::decode_key(id, key)?);
+            }
+        }
+
+        let active =
+            env_var("ACTIVE_ENCRYPTION_KEY_ID").unwrap_or_else(|| DEFAULT_KEY_ID.to_string());
+
+        Ok(Self { active, keys })
+    }
+
+    fn decode_key(id: &str, key: &str) -> Result<Vec<u8>, CredentialError> {
+        STANDARD
+            .decode(key)
+            .map_err(|_| CredentialError::InvalidKey(id.to_string()))
+    }
+
+    fn cipher(&self, id: &str) -> Result<Aes256Gcm, CredentialError> {
+        let key = self
+            .keys
+            .get(id)
+            .ok_or_else(|| CredentialError::MissingKey(id.to_string()))?;
+
+        Aes256Gcm::new_from_slice(key).map_err(|_| CredentialError::InvalidKey(id.to_string()))
+    }
+}
+
+fn keyring() -> Result<&'static Keyring, CredentialError> {
+    KEYRING.as_ref().map_err(Clone::clone)
+}
+
+/// Why stored credentials couldn't be read (or written)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CredentialError {
+    /// The metadata isn't the expected base64, or is too short to hold a nonce
+    BadEncoding,
+    /// Decryption failed the authentication tag check, wrong key or tampered data
+    Authentication,
+    /// Decrypted fine, but isn't `username:password`
+    MalformedPlaintext,
+    /// The key the metadata was encrypted with (or the active key) isn't configured
+    MissingKey(String),
+    /// A configured key isn't a base64 encoded 256 bit key
+    InvalidKey(String),
+    /// The credentials can't be sent as ASCII metadata
+    NonAscii,
+}
+
+impl CredentialError {
+    /// Whether the stored record itself is unreadable (and should be thrown away), as opposed
+    /// to the keys being misconfigured. A failed [`Authentication`] isn't, a wrong or missing
+    /// key fails every record that way.
+    ///
+    /// [`Authentication`]: CredentialError::Authentication
+    pub fn is_corrupt_record(&self) -> bool {
+        matches!(
+            self,
+            CredentialError::BadEncoding | CredentialError::MalformedPlaintext
+        )
+    }
+}
+
+impl From<DecodeError> for CredentialError {
+    fn from(_: DecodeError) -> Self {
+        CredentialError::BadEncoding
+    }
+}
+
+impl std::fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialError::BadEncoding => write!(f, "metadata is not validly encoded"),
+            CredentialError::Authentication => {
+                write!(f, "decryption failed, wrong key or tampered metadata")
+            }
+            CredentialError::MalformedPlaintext => {
+                write!(f, "decrypted metadata is not in the form username:password")
+            }
+            CredentialError::MissingKey(id) => write!(f, "encryption key `{}` not configured", id),
+            CredentialError::InvalidKey(id) => write!(f, "encryption key `{}` is invalid", id),
+            CredentialError::NonAscii => write!(f, "credentials are not valid ASCII metadata"),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
 #[derive(Clone)]
 pub struct Credentials {
     username: String,
@@ -43,8 +164,10 @@ impl Serialize for Credentials {
     where
         S: serde::Serializer,
     {
+        let metadata = self.get_metadata().map_err(serde::ser::Error::custom)?;
+
         let mut state = serializer.serialize_struct("Credentials", 1)?;
-        state.serialize_field("metadata", &self.get_metadata())?;
+        state.serialize_field("metadata", &metadata)?;
 
         state.end()
     }
@@ -72,11 +195,7 @@ impl<'de> serde::Deserialize<'de> for Credentials {
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
 
-                if let Ok(creds) = Credentials::from_metadata(metadata) {
-                    Ok(creds)
-                } else {
-                    Err(serde::de::Error::custom("Bad data received from database"))
-                }
+                Credentials::from_metadata(metadata).map_err(serde::de::Error::custom)
             }
 
             fn visit_map<A>(self, mut map: A) -> std::result::Result<Credentials, A::Error>
@@ -101,11 +220,7 @@ impl<'de> serde::Deserialize<'de> for Credentials {
                 let metadata =
                     metadata.ok_or_else(|| serde::de::Error::missing_field("metadata"))?;
 
-                if let Ok(creds) = Credentials::from_metadata(metadata) {
-                    Ok(creds)
-                } else {
-                    Err(serde::de::Error::custom("Bad data received from database"))
-                }
+                Credentials::from_metadata(metadata).map_err(serde::de::Error::custom)
             }
         }
 
@@ -121,44 +236,93 @@ impl Credentials {
         }
     }
 
-    pub fn get_auth(&self) -> AsciiMetadataValue {
+    pub fn get_auth(&self) -> Result<AsciiMetadataValue, CredentialError> {
         format!(
             "Basic {}",
             URL_SAFE.encode(format!("{}:{}", self.username, self.password))
         )
         .parse()
-        .unwrap()
+        .map_err(|_| CredentialError::NonAscii)
     }
 
-    /// Metadata format (hopefully)
+    /// Metadata format, v1
+    /// `v1.<key id>.` followed by
     /// 0-15: Nonce, Base64 encoded
     /// rest: encrypted creds, Base64 decode before decrypting
-    pub fn get_metadata(&self) -> String {
-        let cipher = Aes256Gcm::new(PRIVATE_KEY.as_slice().into());
+    ///
+    /// The legacy format is the same without the prefix, and always uses [`DEFAULT_KEY_ID`].
+    pub fn get_metadata(&self) -> Result<String, CredentialError> {
+        self.get_metadata_with(keyring()?)
+    }
+
+    pub fn from_metadata(metadata: String) -> Result<Self, CredentialError> {
+        Self::from_metadata_with(&metadata, keyring()?)
+    }
+
+    /// Whether the metadata should be re-encrypted, either because it is in the legacy
+    /// format or because it isn't encrypted with the active key.
+    pub fn needs_rotation(metadata: &str) -> Result<bool, CredentialError> {
+        Ok(Self::needs_rotation_with(metadata, keyring()?))
+    }
+
+    fn get_metadata_with(&self, keyring: &Keyring) -> Result<String, CredentialError> {
+        let cipher = keyring.cipher(&keyring.active)?;
         let (nonce, metadata) =
             Self::encrypt(&cipher, format!("{}:{}", self.username(), self.password()));
 
-        let s = format!("{}{}", STANDARD.encode(nonce), STANDARD.encode(metadata));
-        s
+        Ok(format!(
+            "{}{}.{}{}",
+            METADATA_V1_PREFIX,
+            keyring.active,
+            STANDARD.encode(nonce),
+            STANDARD.encode(metadata)
+        ))
+    }
+
+    fn needs_rotation_with(metadata: &str, keyring: &Keyring) -> bool {
+        match Self::key_id(metadata) {
+            Ok((key_id, _)) => {
+                !metadata.starts_with(METADATA_V1_PREFIX) || key_id != keyring.active
+            }
+            // Unreadable either way, re-encrypting won't help
+            Err(_) => false,
+        }
+    }
+
+    /// Returns (key id, nonce followed by the encrypted creds)
+    fn key_id(metadata: &str) -> Result<(&str, &str), CredentialError> {
+        match metadata.strip_prefix(METADATA_V1_PREFIX) {
+            Some(versioned) => versioned
+                .split_once('.')
+                .ok_or(CredentialError::BadEncoding),
+            // Base64 never contains a `.`, so this can't be mistaken for versioned metadata
+            None => Ok((DEFAULT_KEY_ID, metadata)),
+        }
     }
 
-    pub fn from_metadata(metadata: String) -> Result<Self, DecodeError> {
-        let cipher = Aes256Gcm::new(PRIVATE_KEY.as_slice().into());
+    fn from_metadata_with(metadata: &str, keyring: &Keyring) -> Result<Self, CredentialError> {
+        let (key_id, metadata) = Self::key_id(metadata)?;
+        let cipher = keyring.cipher(key_id)?;
 
         // 12 byte nonce corresponds to 16 length string
         // First 16 bytes encoded into base64 is the nonce
-        let nonce = STANDARD.decode(&metadata[0..NONCE_LENGTH]).unwrap();
-        let secret = STANDARD.decode(&metadata[NONCE_LENGTH..]).unwrap();
-        let secret = Self::decrypt(&cipher, nonce, secret);
+        if metadata.len() < NONCE_LENGTH || !metadata.is_char_boundary(NONCE_LENGTH) {
+            return Err(CredentialError::BadEncoding);
+        }
+        let nonce = STANDARD.decode(&metadata[0..NONCE_LENGTH])?;
+        let secret = STANDARD.decode(&metadata[NONCE_LENGTH..])?;
+        if nonce.len() != NONCE_SIZE {
+            return Err(CredentialError::BadEncoding);
+        }
+        let secret = Self::decrypt(&cipher, nonce, secret)?;
 
-        // 58 is the ASCII code for ':'
-        // metadata is in the form username:password
-        let mut split_at_colon = secret.split(|ascii| ascii == &58u8);
+        // metadata is in the form username:password, the username can't have a colon
+        let secret = String::from_utf8(secret).map_err(|_| CredentialError::MalformedPlaintext)?;
+        let (username, password) = secret
+            .split_once(':')
+            .ok_or(CredentialError::MalformedPlaintext)?;
 
-        Ok(Self::new(
-            String::from_utf8_lossy(split_at_colon.next().unwrap()),
-            String::from_utf8_lossy(split_at_colon.next().unwrap()),
-        ))
+        Ok(Self::new(username, password))
     }
 
     pub fn username(&self) -> &str {
@@ -180,18 +344,22 @@ impl Credentials {
 
         let encrypted_credentials = cipher
             .encrypt(Nonce::from_slice(&nonce), secret.as_ref().as_ref())
-            .unwrap();
+            .expect("AES-GCM only fails to encrypt plaintexts far larger than credentials");
 
         (nonce.into(), encrypted_credentials)
     }
 
-    fn decrypt<S: AsRef<[u8]>>(cipher: &Aes256Gcm, nonce: S, encrypted: S) -> Vec<u8> {
+    fn decrypt<S: AsRef<[u8]>>(
+        cipher: &Aes256Gcm,
+        nonce: S,
+        encrypted: S,
+    ) -> Result<Vec<u8>, CredentialError> {
         cipher
             .decrypt(
                 Nonce::from_slice(nonce.as_ref()),
                 encrypted.as_ref().as_ref(),
             )
-            .unwrap()
+            .map_err(|_| CredentialError::Authentication)
     }
 }
 
@@ -204,3 +372,95 @@ impl From<Credentials> for Bson {
         Bson::Document(doc)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyring(active: &str, ids: &[&str]) -> Keyring {
+        Keyring {
+            active: active.to_string(),
+            keys: ids
+                .iter()
+                .enumerate()
+                .map(|(i, id)| (id.to_string(), vec![i as u8; 32]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn legacy_metadata() {
+        let keyring = keyring("new", &[DEFAULT_KEY_ID, "new"]);
+        let cipher = keyring.cipher(DEFAULT_KEY_ID).unwrap();
+        let (nonce, secret) = Credentials::encrypt(&cipher, "user:pass");
+        let legacy = format!("{}{}", STANDARD.encode(nonce), STANDARD.encode(secret));
+
+        let creds = Credentials::from_metadata_with(&legacy, &keyring).unwrap();
+        assert_eq!(creds.username(), "user");
+        assert_eq!(creds.password(), "pass");
+        assert!(Credentials::needs_rotation_with(&legacy, &keyring));
+    }
+
+    #[test]
+    fn rotation() {
+        let old = keyring("old", &["old"]);
+        let rotated = keyring("new", &["old", "new"]);
+        let creds = Credentials::new("user", "pass");
+
+        let metadata = creds.get_metadata_with(&old).unwrap();
+        assert!(metadata.starts_with("v1.old."));
+        assert!(!Credentials::needs_rotation_with(&metadata, &old));
+        assert!(Credentials::needs_rotation_with(&metadata, &rotated));
+
+        let decrypted = Credentials::from_metadata_with(&metadata, &rotated).unwrap();
+        let metadata = decrypted.get_metadata_with(&rotated).unwrap();
+        assert!(metadata.starts_with("v1.new."));
+        assert!(!Credentials::needs_rotation_with(&metadata, &rotated));
+        assert_eq!(
+            Credentials::from_metadata_with(&metadata, &rotated)
+                .unwrap()
+                .username(),
+            "user"
+        );
+    }
+
+    #[test]
+    fn unreadable_metadata() {
+        let keyring = keyring("new", &["old", "new"]);
+        let metadata = Credentials::new("user", "pass")
+            .get_metadata_with(&keyring)
+            .unwrap();
+
+        let wrong_key = metadata.replacen("v1.new.", "v1.old.", 1);
+        assert_eq!(
+            Credentials::from_metadata_with(&wrong_key, &keyring).err(),
+            Some(CredentialError::Authentication)
+        );
+        assert!(!CredentialError::Authentication.is_corrupt_record());
+
+        let unknown_key = metadata.replacen("v1.new.", "v1.gone.", 1);
+        assert_eq!(
+            Credentials::from_metadata_with(&unknown_key, &keyring).err(),
+            Some(CredentialError::MissingKey(String::from("gone")))
+        );
+
+        for garbage in ["", "v1.new", "v1.new.short", "v1.new.not base64 at all!!"] {
+            assert_eq!(
+                Credentials::from_metadata_with(garbage, &keyring).err(),
+                Some(CredentialError::BadEncoding)
+            );
+        }
+
+        let cipher = keyring.cipher("new").unwrap();
+        let (nonce, secret) = Credentials::encrypt(&cipher, "no colon here");
+        let no_colon = format!(
+            "v1.new.{}{}",
+            STANDARD.encode(nonce),
+            STANDARD.encode(secret)
+        );
+        assert_eq!(
+            Credentials::from_metadata_with(&no_colon, &keyring).err(),
+            Some(CredentialError::MalformedPlaintext)
+        );
+    }
+}
@@ -1,13 +1,23 @@
-use futures::stream::TryStreamExt;
 pub mod credentials;
 use super::client::UserClient;
+use super::store::{StoreError, UserRecord};
 use super::types::*;
-use credentials::Credentials;
-use mongodb::bson::doc;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use credentials::{CredentialError, Credentials};
+use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 
-static DATABSE_NAME: &str = "amibot_users";
-static COLLECTION_NAME: &str = "login_credentials";
+/// Random bytes in a feed token, enough that they can't be guessed
+const FEED_TOKEN_SIZE: usize = 32;
+
+/// What [`User::rotate_credentials`] did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rotation {
+    /// Users re-encrypted with the active key
+    pub rotated: usize,
+    /// Users whose credentials couldn't be decrypted
+    pub skipped: usize,
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct User {
@@ -16,6 +26,9 @@ pub struct User {
 
     #[serde(flatten)]
     pub credentials: Credentials,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    feed_token: Option<String>,
 }
 
 impl User {
@@ -23,97 +36,164 @@ impl User {
         id: S,
         username: S,
         password: S,
-        mongo_client: &DatabaseConnection,
+        store: &DatabaseConnection,
     ) -> DbOperationResult<Self> {
-        if let Some(user) = Self::from_id(id.to_string(), mongo_client).await? {
+        if let Some(user) = Self::from_id(id.to_string(), store).await? {
             Ok(user)
         } else {
-            let db = mongo_client.database(DATABSE_NAME);
-            let creds = db.collection::<User>(COLLECTION_NAME);
             let object = Self {
                 id: id.to_string(),
                 credentials: Credentials::new(username, password),
+                feed_token: None,
             };
-            creds.insert_one(object.clone(), None).await?;
+            store.insert_user(object.to_record()?).await?;
             Ok(object)
         }
     }
 
     pub async fn forget(
         id: impl ToString,
-        mongo_client: &DatabaseConnection,
-    ) -> DbOperationResult<Option<User>> {
-        let db = mongo_client.database(DATABSE_NAME);
-        let creds = db.collection::<User>(COLLECTION_NAME);
-
-        let result = creds
-            .find_one_and_delete(doc! { "_id": id.to_string() }, None)
-            .await;
-
-        Self::sanitize_result(id, mongo_client, result).await
-    }
-
-    async fn sanitize_result(
-        id: impl ToString,
-        mongo_client: &DatabaseConnection,
-        result: DbOperationResult<Option<User>>,
+        store: &DatabaseConnection,
     ) -> DbOperationResult<Option<User>> {
-        if let Err(err) = &result {
-            match err.kind.as_ref() {
-                mongodb::error::ErrorKind::BsonDeserialization(_) => {
-                    // Attempt requery using from_id
-                    User::from_id(id, mongo_client).await
-                }
-                _ => result,
-            }
-        } else {
-            result
-        }
+        store
+            .remove_user(&id.to_string())
+            .await?
+            .map(Self::from_record)
+            .transpose()
     }
 
     pub async fn update<S: ToString>(
         id: S,
         username: S,
         password: S,
-        mongo_client: &DatabaseConnection,
+        store: &DatabaseConnection,
     ) -> DbOperationResult<Option<User>> {
-        let db = mongo_client.database(DATABSE_NAME);
-        let creds = db.collection::<User>(COLLECTION_NAME);
-
-        let result = creds
-            .find_one_and_update(
-                doc! { "_id": id.to_string() },
-                doc! { "$set": { "metadata": Credentials::new(username, password).get_metadata() } },
-                None,
+        store
+            .update_user(
+                &id.to_string(),
+                Credentials::new(username, password).get_metadata()?,
             )
-            .await;
-
-        Self::sanitize_result(id, mongo_client, result).await
+            .await?
+            .map(Self::from_record)
+            .transpose()
     }
 
     pub async fn from_id<S: ToString>(
         id: S,
-        mongo_client: &DatabaseConnection,
+        store: &DatabaseConnection,
     ) -> DbOperationResult<Option<Self>> {
-        let db = mongo_client.database(DATABSE_NAME);
-        let creds = db.collection::<User>(COLLECTION_NAME);
+        store
+            .get_user(&id.to_string())
+            .await?
+            .map(Self::from_record)
+            .transpose()
+    }
 
-        let mut cursor = creds.find(doc! { "_id": id.to_string() }, None).await?;
+    /// Finds the user a calendar feed token belongs to
+    pub async fn from_feed_token(
+        token: &str,
+        store: &DatabaseConnection,
+    ) -> DbOperationResult<Option<Self>> {
+        store
+            .get_user_by_feed_token(token)
+            .await?
+            .map(Self::from_record)
+            .transpose()
+    }
 
-        if let Some(user) = cursor.try_next().await? {
-            Ok(Some(user))
-        } else {
-            Ok(None)
+    /// Gives the user a new calendar feed token, the previous one (if any) stops working.
+    /// Returns the user with the new token, `None` if they aren't stored.
+    pub async fn new_feed_token(
+        id: impl ToString,
+        store: &DatabaseConnection,
+    ) -> DbOperationResult<Option<Self>> {
+        let mut token = [0u8; FEED_TOKEN_SIZE];
+        OsRng.fill_bytes(&mut token);
+
+        store
+            .set_feed_token(&id.to_string(), Some(URL_SAFE_NO_PAD.encode(token)))
+            .await?
+            .map(Self::from_record)
+            .transpose()
+    }
+
+    /// Removes the user's calendar feed token, returns the user as it was before
+    pub async fn revoke_feed_token(
+        id: impl ToString,
+        store: &DatabaseConnection,
+    ) -> DbOperationResult<Option<Self>> {
+        let id = id.to_string();
+        let Some(user) = Self::from_id(&id, store).await? else {
+            return Ok(None);
+        };
+
+        store.set_feed_token(&id, None).await?;
+        Ok(Some(user))
+    }
+
+    /// Re-encrypts every stored user that isn't already in the current metadata format
+    /// under the active key. Records that can't be decrypted, corrupt ones or ones encrypted
+    /// with a key that isn't configured, are left alone and counted as skipped.
+    pub async fn rotate_credentials(store: &DatabaseConnection) -> DbOperationResult<Rotation> {
+        let mut rotation = Rotation::default();
+
+        for record in store.list_users().await? {
+            if Credentials::needs_rotation(&record.metadata)? {
+                let user = match Self::from_record(record) {
+                    Ok(user) => user,
+                    Err(StoreError::Credentials(err))
+                        if err.is_corrupt_record()
+                            || matches!(
+                                err,
+                                CredentialError::Authentication | CredentialError::MissingKey(_)
+                            ) =>
+                    {
+                        rotation.skipped += 1;
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                };
+                store
+                    .update_user(&user.id, user.credentials.get_metadata()?)
+                    .await?;
+                rotation.rotated += 1;
+            }
         }
+
+        Ok(rotation)
+    }
+
+    fn from_record(record: UserRecord) -> DbOperationResult<Self> {
+        Ok(Self {
+            id: record.id,
+            credentials: Credentials::from_metadata(record.metadata)?,
+            feed_token: record.feed_token,
+        })
     }
 
-    pub fn get_client(&self, connection: AmizoneConnection) -> Result<UserClient> {
-        Ok(UserClient::new(self.credentials.get_auth(), connection))
+    fn to_record(&self) -> DbOperationResult<UserRecord> {
+        Ok(UserRecord {
+            id: self.id.clone(),
+            metadata: self.credentials.get_metadata()?,
+            feed_token: self.feed_token.clone(),
+        })
+    }
+
+    pub fn get_client(
+        &self,
+        connection: AmizoneConnection,
+    ) -> std::result::Result<UserClient, CredentialError> {
+        Ok(UserClient::new(self.credentials.get_auth()?, connection))
     }
 
     pub fn id(&self) -> &str {
         &self.id
     }
+
+    /// Secret in the url of the user's calendar feed, if they have one
+    pub fn feed_token(&self) -> Option<&str> {
+        self.feed_token.as_deref()
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +226,7 @@ mod tests {
         let example = User {
             id: ID.to_string(),
             credentials: Credentials::new(USERNAME, PASS),
+            feed_token: None,
         };
 
         println!("{}", serde_json::to_string_pretty(&example).unwrap());
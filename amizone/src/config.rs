@@ -0,0 +1,37 @@
+//! Reading settings from the environment, shared by the crates so variables are read the same
+//! way everywhere.
+
+use std::time::Duration;
+
+/// The variable `key` if it's set and not blank
+pub fn env_var(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// A number of seconds, fractions allowed, for `*_SECS` style variables. It has to be more
+/// than 0, settings that can be turned off with 0 check for it before parsing.
+pub fn parse_secs(secs: &str) -> Result<Duration, String> {
+    secs.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| format!("Invalid number of seconds `{}`", secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secs() {
+        assert_eq!(parse_secs(" 20 "), Ok(Duration::from_secs(20)));
+        assert_eq!(parse_secs("0.5"), Ok(Duration::from_millis(500)));
+
+        for bad in ["0", "-1", "NaN", "inf", "ten", ""] {
+            assert!(parse_secs(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! SGPA and CGPA worked out from course results, and what they would be with made up grades
+//! for some courses ("what if I get an A in this one?").
+//!
+//! Every grade point is weighed by the effective credits of its course, CGPA is the same
+//! average over every semester up to and including the one asked for.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::model::CourseResult;
+
+/// Letter grades on Amity's 10 point scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Grade {
+    APlus,
+    A,
+    BPlus,
+    B,
+    CPlus,
+    C,
+    D,
+    F,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 8] = [
+        Grade::APlus,
+        Grade::A,
+        Grade::BPlus,
+        Grade::B,
+        Grade::CPlus,
+        Grade::C,
+        Grade::D,
+        Grade::F,
+    ];
+
+    pub fn grade_point(&self) -> u32 {
+        match self {
+            Grade::APlus => 10,
+            Grade::A => 9,
+            Grade::BPlus => 8,
+            Grade::B => 7,
+            Grade::CPlus => 6,
+            Grade::C => 5,
+            Grade::D => 4,
+            Grade::F => 0,
+        }
+    }
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Grade::APlus => "A+",
+            Grade::A => "A",
+            Grade::BPlus => "B+",
+            Grade::B => "B",
+            Grade::CPlus => "C+",
+            Grade::C => "C",
+            Grade::D => "D",
+            Grade::F => "F",
+        })
+    }
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    /// Parses a letter grade like `A+` or `b`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        Grade::ALL
+            .into_iter()
+            .find(|grade| grade.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("`{}` isn't a grade", s))
+    }
+}
+
+/// Credits and credit weighted grade points of some courses put together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GradePoints {
+    pub credits: u32,
+    pub points: u32,
+}
+
+impl GradePoints {
+    pub fn add(&mut self, credits: u32, grade_point: u32) {
+        self.credits += credits;
+        self.points += credits * grade_point;
+    }
+
+    /// `None` without any credits
+    pub fn average(&self) -> Option<f64> {
+        (self.credits > 0).then(|| self.points as f64 / self.credits as f64)
+    }
+}
+
+impl<'a> FromIterator<&'a CourseResult> for GradePoints {
+    fn from_iter<T: IntoIterator<Item = &'a CourseResult>>(iter: T) -> Self {
+        let mut total = Self::default();
+        for result in iter {
+            total.add(
+                result.credits.effective.max(0) as u32,
+                result.grade_point.max(0) as u32,
+            );
+        }
+        total
+    }
+}
+
+/// A grade someone expects (or hopes) to get in a course
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectedCourse {
+    /// Course code, a course with published results is replaced by the projection
+    pub code: String,
+    pub grade: Grade,
+    pub credits: u32,
+}
+
+/// SGPA and CGPA of a semester with some grades made up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhatIf {
+    pub semester: usize,
+    pub sgpa: Option<f64>,
+    pub cgpa: Option<f64>,
+}
+
+/// Course results of every semester
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    semesters: BTreeMap<usize, Vec<CourseResult>>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the results of `semester`
+    pub fn insert(&mut self, semester: usize, results: Vec<CourseResult>) -> &mut Self {
+        self.semesters.insert(semester, results);
+        self
+    }
+
+    pub fn semester(&self, semester: usize) -> &[CourseResult] {
+        self.semesters
+            .get(&semester)
+            .map_or(&[], |results| results.as_slice())
+    }
+
+    pub fn sgpa(&self, semester: usize) -> Option<f64> {
+        self.semester(semester)
+            .iter()
+            .collect::<GradePoints>()
+            .average()
+    }
+
+    /// CGPA up to and including `semester`
+    pub fn cgpa(&self, semester: usize) -> Option<f64> {
+        self.semesters
+            .range(..=semester)
+            .flat_map(|(_, results)| results.iter())
+            .collect::<GradePoints>()
+            .average()
+    }
+
+    /// SGPA and CGPA of `semester` with the `projected` grades, courses of the semester that
+    /// aren't projected keep the grades they have.
+    pub fn what_if(&self, semester: usize, projected: &[ProjectedCourse]) -> WhatIf {
+        let is_projected = |result: &&CourseResult| {
+            projected
+                .iter()
+                .any(|course| course.code.eq_ignore_ascii_case(&result.course.code))
+        };
+
+        let mut this_semester = self
+            .semester(semester)
+            .iter()
+            .filter(|result| !is_projected(result))
+            .collect::<GradePoints>();
+        for course in projected {
+            this_semester.add(course.credits, course.grade.grade_point());
+        }
+
+        let before = self
+            .semesters
+            .range(..semester)
+            .flat_map(|(_, results)| results.iter())
+            .collect::<GradePoints>();
+
+        WhatIf {
+            semester,
+            sgpa: this_semester.average(),
+            cgpa: GradePoints {
+                credits: before.credits + this_semester.credits,
+                points: before.points + this_semester.points,
+            }
+            .average(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CourseRef, Credits};
+
+    fn result(code: &str, grade_point: i32, effective: i32) -> CourseResult {
+        CourseResult {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            grade: String::new(),
+            grade_point,
+            max_grade_point: 10,
+            credits: Credits {
+                acquired: effective,
+                effective,
+                points: grade_point * effective,
+            },
+            published: None,
+        }
+    }
+
+    fn transcript() -> Transcript {
+        let mut transcript = Transcript::new();
+        transcript
+            .insert(1, vec![result("A", 10, 4), result("B", 7, 2)])
+            .insert(2, vec![result("C", 8, 3), result("D", 0, 0)]);
+        transcript
+    }
+
+    #[test]
+    fn grades() {
+        assert_eq!("a+".parse(), Ok(Grade::APlus));
+        assert_eq!(" B ".parse(), Ok(Grade::B));
+        assert!("E".parse::<Grade>().is_err());
+        assert_eq!(Grade::CPlus.to_string(), "C+");
+    }
+
+    #[test]
+    fn gpas() {
+        let transcript = transcript();
+
+        // (40 + 14) / 6
+        assert_eq!(transcript.sgpa(1), Some(9.0));
+        // Courses without credits don't count
+        assert_eq!(transcript.sgpa(2), Some(8.0));
+        // (40 + 14 + 24) / 9
+        assert_eq!(
+            transcript.cgpa(2).map(|cgpa| (cgpa * 100.0).round()),
+            Some(867.0)
+        );
+        assert_eq!(transcript.sgpa(3), None);
+        assert_eq!(transcript.cgpa(3), transcript.cgpa(2));
+    }
+
+    #[test]
+    fn what_if() {
+        let transcript = transcript();
+
+        let projected = [
+            ProjectedCourse {
+                code: "E".into(),
+                grade: Grade::A,
+                credits: 3,
+            },
+            ProjectedCourse {
+                code: "F".into(),
+                grade: Grade::F,
+                credits: 3,
+            },
+        ];
+        let what_if = transcript.what_if(3, &projected);
+        assert_eq!(what_if.sgpa, Some(4.5));
+        // (78 + 27) / 15
+        assert_eq!(what_if.cgpa, Some(7.0));
+
+        // Projections replace published results of the same course
+        let projected = [ProjectedCourse {
+            code: "c".into(),
+            grade: Grade::APlus,
+            credits: 3,
+        }];
+        let what_if = transcript.what_if(2, &projected);
+        assert_eq!(what_if.sgpa, Some(10.0));
+        assert_eq!(what_if.cgpa, Some(84.0 / 9.0));
+    }
+}
@@ -0,0 +1,314 @@
+//! Export of classes and exams as an iCalendar ([RFC 5545](https://www.rfc-editor.org/rfc/rfc5545))
+//! file, for importing into calendar apps.
+//!
+//! Every event gets a UID derived from what it is (the course and start of a class, the course
+//! of an exam and which of its exams it is), so importing a newer export updates the events
+//! instead of duplicating them.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::model::{ClassSlot, Exam};
+
+/// Hours an exam is assumed to take, go-amizone only knows when it starts
+pub const EXAM_HOURS: i64 = 3;
+
+/// Lines are folded past this many octets, not counting the CRLF
+const MAX_LINE_LENGTH: usize = 75;
+
+const UID_DOMAIN: &str = "amibot";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Event {
+    uid: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    summary: String,
+    location: Option<String>,
+    description: String,
+    category: &'static str,
+}
+
+/// A calendar of classes and exams, rendered to iCalendar text with [`Display`]
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    name: String,
+    /// When the calendar was made, the `DTSTAMP` of every event
+    stamp: DateTime<Utc>,
+    events: Vec<Event>,
+    /// Exams added of every course, by code
+    exams: HashMap<String, usize>,
+}
+
+impl Calendar {
+    pub fn new(name: impl ToString) -> Self {
+        Self::with_stamp(name, Utc::now())
+    }
+
+    pub fn with_stamp(name: impl ToString, stamp: DateTime<Utc>) -> Self {
+        Self {
+            name: name.to_string(),
+            stamp,
+            events: Vec::new(),
+            exams: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn add_class(&mut self, class: &ClassSlot) -> &mut Self {
+        let start = class.start.with_timezone(&Utc);
+
+        let mut description = format!("Course code: {}", class.course.code);
+        if !class.faculty.is_empty() {
+            write!(description, "\nFaculty: {}", class.faculty).unwrap();
+        }
+
+        self.events.push(Event {
+            uid: uid("class", &class.course.code, Some(&timestamp(start))),
+            start,
+            end: class.end.with_timezone(&Utc),
+            summary: format!("{} ({})", class.course.name, class.course.code),
+            location: Some(class.room.clone()).filter(|room| !room.is_empty()),
+            description,
+            category: "Class",
+        });
+        self
+    }
+
+    pub fn add_exam(&mut self, exam: &Exam) -> &mut Self {
+        let start = exam.time.with_timezone(&Utc);
+
+        let mut description = format!("Course code: {}", exam.course.code);
+        if !exam.mode.is_empty() {
+            write!(description, "\nMode: {}", exam.mode).unwrap();
+        }
+
+        // Keyed by the course, a rescheduled exam moves instead of showing up twice. A course
+        // can have more than one exam on a datesheet (theory and practical), the ones after
+        // the first are told apart by their order.
+        let nth = self.exams.entry(exam.course.code.clone()).or_default();
+        *nth += 1;
+        let nth = (*nth > 1).then(|| nth.to_string());
+
+        self.events.push(Event {
+            uid: uid("exam", &exam.course.code, nth.as_deref()),
+            start,
+            end: start + Duration::hours(EXAM_HOURS),
+            summary: format!("Exam: {} ({})", exam.course.name, exam.course.code),
+            location: exam.location.clone(),
+            description,
+            category: "Exam",
+        });
+        self
+    }
+}
+
+/// Stable identifier of an event, from what kind it is, its course and what tells it apart
+/// from the course's other events of that kind, if it can have more than one
+fn uid(kind: &str, code: &str, discriminator: Option<&str>) -> String {
+    let code = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+
+    match discriminator {
+        Some(discriminator) => format!("{}-{}-{}@{}", kind, code, discriminator, UID_DOMAIN),
+        None => format!("{}-{}@{}", kind, code, UID_DOMAIN),
+    }
+}
+
+/// UTC date-time form, eg. `20230515T033000Z`
+fn timestamp<Tz: TimeZone>(time: DateTime<Tz>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escapes a TEXT value
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a content line, folded into lines of at most 75 octets without splitting a
+/// character, each ending with CRLF.
+fn write_line(f: &mut impl Write, name: &str, value: &str) -> std::fmt::Result {
+    let line = format!("{}:{}", name, value);
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            f.write_str("\r\n ")?;
+            // The leading space counts towards the folded line
+            length = 1;
+        }
+        f.write_char(c)?;
+        length += c.len_utf8();
+    }
+
+    f.write_str("\r\n")
+}
+
+impl Display for Calendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_line(f, "BEGIN", "VCALENDAR")?;
+        write_line(f, "VERSION", "2.0")?;
+        write_line(f, "PRODID", "-//amibot//Amizone export//EN")?;
+        write_line(f, "CALSCALE", "GREGORIAN")?;
+        write_line(f, "METHOD", "PUBLISH")?;
+        write_line(f, "X-WR-CALNAME", &escape(&self.name))?;
+
+        for event in self.events.iter() {
+            write_line(f, "BEGIN", "VEVENT")?;
+            write_line(f, "UID", &event.uid)?;
+            write_line(f, "DTSTAMP", &timestamp(self.stamp))?;
+            write_line(f, "DTSTART", &timestamp(event.start))?;
+            write_line(f, "DTEND", &timestamp(event.end))?;
+            write_line(f, "SUMMARY", &escape(&event.summary))?;
+            if let Some(ref location) = event.location {
+                write_line(f, "LOCATION", &escape(location))?;
+            }
+            write_line(f, "DESCRIPTION", &escape(&event.description))?;
+            write_line(f, "CATEGORIES", event.category)?;
+            write_line(f, "END", "VEVENT")?;
+        }
+
+        write_line(f, "END", "VCALENDAR")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::AttendanceState;
+    use crate::model::{ist, CourseRef};
+
+    fn course() -> CourseRef {
+        CourseRef {
+            code: "CSE101".into(),
+            name: "Programming, in C".into(),
+        }
+    }
+
+    fn class() -> ClassSlot {
+        let start = ist().with_ymd_and_hms(2023, 5, 15, 9, 0, 0).unwrap();
+        ClassSlot {
+            course: course(),
+            start,
+            end: start + Duration::minutes(55),
+            faculty: "Dr. Sample".into(),
+            room: "E2-101".into(),
+            attendance: AttendanceState::Pending,
+        }
+    }
+
+    fn calendar() -> Calendar {
+        Calendar::with_stamp(
+            "Amizone",
+            Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn class_event() {
+        let mut calendar = calendar();
+        calendar.add_class(&class());
+        let ics = calendar.to_string();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nUID:class-CSE101-20230515T033000Z@amibot\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20230501T000000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20230515T033000Z\r\nDTEND:20230515T042500Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Programming\\, in C (CSE101)\r\n"));
+        assert!(ics.contains("\r\nLOCATION:E2-101\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:Course code: CSE101\\nFaculty: Dr. Sample\r\n"));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn exam_event() {
+        let exam = Exam {
+            course: course(),
+            time: ist().with_ymd_and_hms(2023, 5, 22, 10, 0, 0).unwrap(),
+            mode: "Offline".into(),
+            location: Some("Block E; Room 5".into()),
+        };
+
+        let mut calendar = calendar();
+        calendar.add_exam(&exam);
+        let first = calendar.to_string();
+
+        assert!(first.contains("\r\nUID:exam-CSE101@amibot\r\n"));
+        assert!(first.contains("\r\nDTEND:20230522T073000Z\r\n"));
+        assert!(first.contains("\r\nLOCATION:Block E\\; Room 5\r\n"));
+
+        // Rescheduled, still the same event
+        let mut calendar = self::calendar();
+        calendar.add_exam(&Exam {
+            time: exam.time + Duration::days(1),
+            ..exam
+        });
+        assert!(calendar
+            .to_string()
+            .contains("\r\nUID:exam-CSE101@amibot\r\n"));
+    }
+
+    #[test]
+    fn exams_of_one_course() {
+        let theory = Exam {
+            course: course(),
+            time: ist().with_ymd_and_hms(2023, 5, 22, 10, 0, 0).unwrap(),
+            mode: "Offline".into(),
+            location: None,
+        };
+        let practical = Exam {
+            time: theory.time + Duration::days(3),
+            ..theory.clone()
+        };
+
+        let mut calendar = calendar();
+        calendar.add_exam(&theory).add_exam(&practical);
+        let ics = calendar.to_string();
+
+        assert_eq!(calendar.len(), 2);
+        assert!(ics.contains("\r\nUID:exam-CSE101@amibot\r\n"));
+        assert!(ics.contains("\r\nUID:exam-CSE101-2@amibot\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut class = class();
+        class.faculty = "Dr. ".to_string() + &"ñ".repeat(80);
+
+        let mut calendar = calendar();
+        calendar.add_class(&class);
+        let ics = calendar.to_string();
+
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_LENGTH, "{:?} is too long", line);
+        }
+
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("Faculty: Dr. {}\r\n", "ñ".repeat(80))));
+    }
+}
@@ -1,18 +1,47 @@
 pub mod api;
+pub mod config;
+pub mod gpa;
+pub mod ics;
+pub mod model;
+pub mod planner;
+
+#[cfg(feature = "test-support")]
+pub mod testing;
 
 #[cfg(test)]
 mod test {
-    use super::api::{new_db_connection, user::User};
+    use super::api::{
+        new_db_connection,
+        store::{CredentialStore, Documents, UserRecord},
+        types::DatabaseConnection,
+        user::{Rotation, User},
+    };
     use dotenv::dotenv;
 
-    #[tokio::test]
-    async fn crud() {
+    /// Fall back to a throwaway key so the store tests don't need a `.env`
+    fn ensure_key() {
+        dotenv().ok();
+        if std::env::var("PRIVATE_ENCRYPTION_KEY").is_err() {
+            std::env::set_var(
+                "PRIVATE_ENCRYPTION_KEY",
+                "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+            );
+        }
+    }
+
+    /// The mongodb server at `DATABASE_URL`, its tests are ignored as there's none by default
+    async fn mongo() -> DatabaseConnection {
         dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("missing `DATABASE_URL`");
+        assert!(
+            url.starts_with("mongodb"),
+            "`DATABASE_URL` isn't a mongodb url"
+        );
+        new_db_connection(url).await.unwrap()
+    }
 
+    async fn crud(client: DatabaseConnection) {
         const ID: &'static str = "486590017150517248";
-        let client = new_db_connection(std::env::var("DATABASE_URL").unwrap().to_string())
-            .await
-            .unwrap();
 
         User::new(ID, "samepluser", "samplepass", &client)
             .await
@@ -21,6 +50,7 @@ mod test {
         let new_user = User::from_id(ID, &client).await.unwrap().unwrap();
 
         assert_eq!(new_user.id(), ID);
+        assert_eq!(new_user.credentials.username(), "samepluser");
 
         let updated_user = User::update(ID, "samepluserupdated", "samplepassupdated", &client)
             .await
@@ -30,5 +60,181 @@ mod test {
         assert_eq!(updated_user.credentials.username(), "samepluserupdated");
 
         User::forget(ID, &client).await.unwrap();
+        assert!(User::from_id(ID, &client).await.unwrap().is_none());
+    }
+
+    async fn feed_tokens(client: DatabaseConnection) {
+        const ID: &'static str = "486590017150517249";
+
+        User::new(ID, "samepluser", "samplepass", &client)
+            .await
+            .unwrap();
+        assert!(User::new_feed_token("unknown", &client)
+            .await
+            .unwrap()
+            .is_none());
+
+        let user = User::new_feed_token(ID, &client).await.unwrap().unwrap();
+        let token = user.feed_token().unwrap().to_string();
+        assert!(token.len() >= 43);
+
+        let found = User::from_feed_token(&token, &client)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id(), ID);
+
+        // Rotating and updating credentials keep exactly one working token
+        let rotated = User::new_feed_token(ID, &client).await.unwrap().unwrap();
+        assert_ne!(rotated.feed_token().unwrap(), token);
+        assert!(User::from_feed_token(&token, &client)
+            .await
+            .unwrap()
+            .is_none());
+
+        let updated = User::update(ID, "samepluser", "newpass", &client)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.feed_token(), rotated.feed_token());
+
+        User::revoke_feed_token(ID, &client).await.unwrap().unwrap();
+        assert!(
+            User::from_feed_token(rotated.feed_token().unwrap(), &client)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(User::from_id(ID, &client)
+            .await
+            .unwrap()
+            .unwrap()
+            .feed_token()
+            .is_none());
+    }
+
+    async fn documents(client: DatabaseConnection) {
+        let numbers = Documents::<Vec<u32>>::new(client.clone(), "numbers");
+        let names = Documents::<String>::new(client, "names");
+
+        numbers.put("a", &vec![1, 2]).await.unwrap();
+        numbers.put("b", &vec![3]).await.unwrap();
+        numbers.put("a", &vec![4]).await.unwrap();
+        names.put("a", &"four".to_string()).await.unwrap();
+
+        assert_eq!(numbers.get("a").await.unwrap(), Some(vec![4]));
+        assert_eq!(names.get("a").await.unwrap().as_deref(), Some("four"));
+        assert_eq!(numbers.get("c").await.unwrap(), None);
+
+        let mut all = numbers.list().await.unwrap();
+        all.sort();
+        assert_eq!(all, vec![("a".into(), vec![4]), ("b".into(), vec![3])]);
+
+        assert_eq!(numbers.remove("b").await.unwrap(), Some(vec![3]));
+        assert_eq!(numbers.remove("b").await.unwrap(), None);
+        assert_eq!(numbers.list().await.unwrap().len(), 1);
+        assert_eq!(names.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rotation_skips_unreadable_records() {
+        ensure_key();
+        let client = new_db_connection("memory://").await.unwrap();
+
+        // Back to the legacy format, which is always under the default key
+        User::new("1", "samepluser", "samplepass", &client)
+            .await
+            .unwrap();
+        let metadata = client.get_user("1").await.unwrap().unwrap().metadata;
+        let (_, legacy) = metadata.rsplit_once('.').unwrap();
+        client.update_user("1", legacy.to_string()).await.unwrap();
+
+        for (id, metadata) in [
+            ("2", "v1.gone.AAAAAAAAAAAAAAAAAAAAAAAA"),
+            ("3", "v1.broken"),
+        ] {
+            client
+                .insert_user(UserRecord {
+                    id: id.into(),
+                    metadata: metadata.into(),
+                    feed_token: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        // A record of a key that isn't configured doesn't stop the rest from being rotated
+        let rotation = User::rotate_credentials(&client).await.unwrap();
+        assert_eq!(
+            rotation,
+            Rotation {
+                rotated: 1,
+                skipped: 1
+            }
+        );
+
+        let user = User::from_id("1", &client).await.unwrap().unwrap();
+        assert_eq!(user.credentials.username(), "samepluser");
+        assert!(client
+            .get_user("1")
+            .await
+            .unwrap()
+            .unwrap()
+            .metadata
+            .starts_with("v1."));
+    }
+
+    #[tokio::test]
+    async fn crud_memory() {
+        ensure_key();
+        crud(new_db_connection("memory://").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn crud_sqlite() {
+        ensure_key();
+        crud(new_db_connection("sqlite::memory:").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn feed_tokens_memory() {
+        ensure_key();
+        feed_tokens(new_db_connection("memory://").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn feed_tokens_sqlite() {
+        ensure_key();
+        feed_tokens(new_db_connection("sqlite::memory:").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn documents_memory() {
+        documents(new_db_connection("memory://").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn documents_sqlite() {
+        documents(new_db_connection("sqlite::memory:").await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a mongodb server at `DATABASE_URL`"]
+    async fn crud_mongo() {
+        ensure_key();
+        crud(mongo().await).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a mongodb server at `DATABASE_URL`"]
+    async fn feed_tokens_mongo() {
+        ensure_key();
+        feed_tokens(mongo().await).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a mongodb server at `DATABASE_URL`"]
+    async fn documents_mongo() {
+        documents(mongo().await).await;
     }
 }
@@ -0,0 +1,562 @@
+//! Validated, ergonomic versions of the types go-amizone responds with.
+//!
+//! Prost makes every embedded message optional and every timestamp a
+//! [`prost_types::Timestamp`], the types here are converted from those with [`TryFrom`], which
+//! fails with a [`ModelError`] naming whatever was missing instead of defaulting it.
+//!
+//! ```ignore
+//! let records = client
+//!     .get_attendance()
+//!     .await?
+//!     .into_iter()
+//!     .map(model::AttendanceRecord::try_from)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! ```
+
+use std::fmt::Display;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeZone, Utc};
+
+use crate::api::types::{go_amizone::server::proto::v1 as goamizone, *};
+
+/// Amizone's timezone, every time it deals with is in IST
+pub fn ist() -> FixedOffset {
+    FixedOffset::east_opt(5 * 60 * 60 + 30 * 60).unwrap()
+}
+
+/// Today's date in IST
+pub fn today() -> NaiveDate {
+    Utc::now().with_timezone(&ist()).date_naive()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ModelError {
+    /// go-amizone left out a field that should always be there
+    MissingField {
+        message: &'static str,
+        field: &'static str,
+    },
+    /// A field is there but makes no sense, like a negative count or an impossible date
+    InvalidField {
+        message: &'static str,
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelError::MissingField { message, field } => {
+                write!(f, "{}.{} is missing", message, field)
+            }
+            ModelError::InvalidField {
+                message,
+                field,
+                value,
+            } => write!(f, "{}.{} has an invalid value `{}`", message, field, value),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+fn required<T>(
+    value: Option<T>,
+    message: &'static str,
+    field: &'static str,
+) -> Result<T, ModelError> {
+    value.ok_or(ModelError::MissingField { message, field })
+}
+
+fn invalid(message: &'static str, field: &'static str, value: impl ToString) -> ModelError {
+    ModelError::InvalidField {
+        message,
+        field,
+        value: value.to_string(),
+    }
+}
+
+fn count(value: i32, message: &'static str, field: &'static str) -> Result<u32, ModelError> {
+    u32::try_from(value).map_err(|_| invalid(message, field, value))
+}
+
+fn timestamp(
+    value: Option<prost_types::Timestamp>,
+    message: &'static str,
+    field: &'static str,
+) -> Result<DateTime<FixedOffset>, ModelError> {
+    let value = required(value, message, field)?;
+
+    u32::try_from(value.nanos)
+        .ok()
+        .and_then(|nanos| Utc.timestamp_opt(value.seconds, nanos).single())
+        .map(|time| time.with_timezone(&ist()))
+        .ok_or_else(|| invalid(message, field, value))
+}
+
+/// Dates (of birth, validity) are sent as midnight UTC timestamps
+fn date(
+    value: Option<prost_types::Timestamp>,
+    message: &'static str,
+    field: &'static str,
+) -> Result<NaiveDate, ModelError> {
+    Ok(timestamp(value, message, field)?.naive_utc().date())
+}
+
+impl TryFrom<Date> for NaiveDate {
+    type Error = ModelError;
+
+    fn try_from(value: Date) -> Result<Self, Self::Error> {
+        u32::try_from(value.month)
+            .ok()
+            .zip(u32::try_from(value.day).ok())
+            .and_then(|(month, day)| NaiveDate::from_ymd_opt(value.year, month, day))
+            .ok_or_else(|| {
+                invalid(
+                    "Date",
+                    "date",
+                    format!("{}-{}-{}", value.year, value.month, value.day),
+                )
+            })
+    }
+}
+
+impl From<NaiveDate> for Date {
+    fn from(value: NaiveDate) -> Self {
+        Date {
+            year: value.year(),
+            month: value.month() as i32,
+            day: value.day() as i32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CourseRef {
+    pub code: String,
+    pub name: String,
+}
+
+impl From<goamizone::CourseRef> for CourseRef {
+    fn from(value: goamizone::CourseRef) -> Self {
+        Self {
+            code: value.code,
+            name: value.name,
+        }
+    }
+}
+
+impl Display for CourseRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.code)
+    }
+}
+
+/// Classes attended out of the ones held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Attendance {
+    pub attended: u32,
+    pub held: u32,
+}
+
+impl Attendance {
+    /// `None` if no classes have been held yet
+    pub fn percentage(&self) -> Option<f64> {
+        match self.held {
+            0 => None,
+            held => Some(self.attended as f64 / held as f64 * 100.0),
+        }
+    }
+
+    /// Whether the attendance is at or above `threshold` percent, trivially true before any
+    /// classes are held
+    pub fn meets(&self, threshold: f64) -> bool {
+        self.percentage()
+            .map_or(true, |percentage| percentage >= threshold)
+    }
+
+    pub fn missed(&self) -> u32 {
+        self.held.saturating_sub(self.attended)
+    }
+}
+
+impl TryFrom<goamizone::Attendance> for Attendance {
+    type Error = ModelError;
+
+    fn try_from(value: goamizone::Attendance) -> Result<Self, Self::Error> {
+        let attendance = Self {
+            attended: count(value.attended, "Attendance", "attended")?,
+            held: count(value.held, "Attendance", "held")?,
+        };
+
+        if attendance.attended > attendance.held {
+            return Err(invalid(
+                "Attendance",
+                "attended",
+                format!("{}/{}", attendance.attended, attendance.held),
+            ));
+        }
+
+        Ok(attendance)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttendanceRecord {
+    pub course: CourseRef,
+    pub attendance: Attendance,
+}
+
+impl TryFrom<goamizone::AttendanceRecord> for AttendanceRecord {
+    type Error = ModelError;
+
+    fn try_from(value: goamizone::AttendanceRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            course: required(value.course, "AttendanceRecord", "course")?.into(),
+            attendance: required(value.attendance, "AttendanceRecord", "attendance")?.try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marks {
+    pub have: f32,
+    pub max: f32,
+}
+
+impl From<goamizone::Marks> for Marks {
+    fn from(value: goamizone::Marks) -> Self {
+        Self {
+            have: value.have,
+            max: value.max,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Course {
+    pub course: CourseRef,
+    /// Core, elective, etc.
+    pub kind: String,
+    /// Not tracked for some courses
+    pub attendance: Option<Attendance>,
+    /// Not out for every course
+    pub internal_marks: Option<Marks>,
+    /// Link to the syllabus, if amizone has one
+    pub syllabus: Option<String>,
+}
+
+impl TryFrom<goamizone::Course> for Course {
+    type Error = ModelError;
+
+    fn try_from(value: goamizone::Course) -> Result<Self, Self::Error> {
+        Ok(Self {
+            course: required(value.r#ref, "Course", "ref")?.into(),
+            kind: value.r#type,
+            attendance: value.attendance.map(TryInto::try_into).transpose()?,
+            internal_marks: value.internal_marks.map(Into::into),
+            syllabus: Some(value.syllabus_doc).filter(|doc| !doc.is_empty()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassSlot {
+    pub course: CourseRef,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub faculty: String,
+    pub room: String,
+    pub attendance: AttendanceState,
+}
+
+impl TryFrom<ScheduledClass> for ClassSlot {
+    type Error = ModelError;
+
+    fn try_from(value: ScheduledClass) -> Result<Self, Self::Error> {
+        Ok(Self {
+            course: required(value.course, "ScheduledClass", "course")?.into(),
+            start: timestamp(value.start_time, "ScheduledClass", "start_time")?,
+            end: timestamp(value.end_time, "ScheduledClass", "end_time")?,
+            faculty: value.faculty,
+            room: value.room,
+            attendance: value.attendance.into(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exam {
+    pub course: CourseRef,
+    pub time: DateTime<FixedOffset>,
+    pub mode: String,
+    pub location: Option<String>,
+}
+
+impl TryFrom<ScheduledExam> for Exam {
+    type Error = ModelError;
+
+    fn try_from(value: ScheduledExam) -> Result<Self, Self::Error> {
+        Ok(Self {
+            course: required(value.course, "ScheduledExam", "course")?.into(),
+            time: timestamp(value.time, "ScheduledExam", "time")?,
+            mode: value.mode,
+            location: value.location.filter(|location| !location.is_empty()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub enrollment_number: String,
+    pub enrollment_validity: NaiveDate,
+    pub batch: String,
+    pub program: String,
+    pub date_of_birth: NaiveDate,
+    pub blood_group: String,
+    pub id_card_number: String,
+    pub uuid: String,
+}
+
+impl Profile {
+    /// The photo on the student's ID card
+    pub fn photo_url(&self) -> String {
+        format!(
+            "https://img.amizone.net/AWSImageNew.ashx?Type=7&amp;ID={}",
+            self.uuid
+        )
+    }
+}
+
+impl TryFrom<AmizoneProfile> for Profile {
+    type Error = ModelError;
+
+    fn try_from(value: AmizoneProfile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            enrollment_validity: date(value.enrollment_validity, "Profile", "enrollment_validity")?,
+            date_of_birth: date(value.date_of_birth, "Profile", "date_of_birth")?,
+            name: value.name,
+            enrollment_number: value.enrollment_number,
+            batch: value.batch,
+            program: value.program,
+            blood_group: value.blood_group,
+            id_card_number: value.id_card_number,
+            uuid: value.uuid,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Semester {
+    pub name: String,
+    pub number: usize,
+}
+
+impl TryFrom<goamizone::Semester> for Semester {
+    type Error = ModelError;
+
+    fn try_from(value: goamizone::Semester) -> Result<Self, Self::Error> {
+        Ok(Self {
+            number: value
+                .r#ref
+                .parse()
+                .map_err(|_| invalid("Semester", "ref", &value.r#ref))?,
+            name: value.name,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Credits {
+    pub acquired: i32,
+    pub effective: i32,
+    pub points: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CourseResult {
+    pub course: CourseRef,
+    pub grade: String,
+    pub grade_point: i32,
+    pub max_grade_point: i32,
+    pub credits: Credits,
+    pub published: Option<NaiveDate>,
+}
+
+impl TryFrom<ExamResultRecord> for CourseResult {
+    type Error = ModelError;
+
+    fn try_from(value: ExamResultRecord) -> Result<Self, Self::Error> {
+        let score = required(value.score, "ExamResultRecord", "score")?;
+        let credits = required(value.credits, "ExamResultRecord", "credits")?;
+
+        Ok(Self {
+            course: required(value.course, "ExamResultRecord", "course")?.into(),
+            grade: score.grade,
+            grade_point: score.grade_point,
+            max_grade_point: score.max,
+            credits: Credits {
+                acquired: credits.acquired,
+                effective: credits.effective,
+                points: credits.points,
+            },
+            published: value.publish_date.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+/// SGPA and CGPA of a semester
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SemesterGrades {
+    pub semester: usize,
+    pub sgpa: f32,
+    pub cgpa: f32,
+}
+
+impl TryFrom<OverallResult> for SemesterGrades {
+    type Error = ModelError;
+
+    fn try_from(value: OverallResult) -> Result<Self, Self::Error> {
+        let semester = required(value.semester, "OverallResult", "semester")?.semester_ref;
+
+        Ok(Self {
+            semester: semester
+                .parse()
+                .map_err(|_| invalid("OverallResult", "semester", semester))?,
+            sgpa: value.semester_grade_point_average,
+            cgpa: value.cumulative_grade_point_average,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExamResults {
+    pub courses: Vec<CourseResult>,
+    /// One per semester with published results
+    pub overall: Vec<SemesterGrades>,
+}
+
+impl ExamResults {
+    pub fn for_semester(&self, semester: usize) -> Option<&SemesterGrades> {
+        self.overall
+            .iter()
+            .find(|grades| grades.semester == semester)
+    }
+}
+
+impl TryFrom<ExamResultRecords> for ExamResults {
+    type Error = ModelError;
+
+    fn try_from(value: ExamResultRecords) -> Result<Self, Self::Error> {
+        Ok(Self {
+            courses: value
+                .course_wise
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            overall: value
+                .overall
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attendance() {
+        let attendance = Attendance::try_from(goamizone::Attendance {
+            attended: 30,
+            held: 40,
+        })
+        .unwrap();
+
+        assert_eq!(attendance.percentage(), Some(75.0));
+        assert!(attendance.meets(75.0));
+        assert!(!attendance.meets(75.1));
+        assert_eq!(attendance.missed(), 10);
+        assert_eq!(Attendance::default().percentage(), None);
+
+        assert!(Attendance::try_from(goamizone::Attendance {
+            attended: -1,
+            held: 40
+        })
+        .is_err());
+        assert!(Attendance::try_from(goamizone::Attendance {
+            attended: 41,
+            held: 40
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn missing_fields() {
+        let err = AttendanceRecord::try_from(goamizone::AttendanceRecord {
+            course: None,
+            attendance: Some(goamizone::Attendance {
+                attended: 1,
+                held: 1,
+            }),
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            ModelError::MissingField {
+                message: "AttendanceRecord",
+                field: "course"
+            }
+        );
+    }
+
+    #[test]
+    fn class_times_are_ist() {
+        let class = ClassSlot::try_from(ScheduledClass {
+            course: Some(goamizone::CourseRef {
+                code: "CSE101".into(),
+                name: "Programming".into(),
+            }),
+            // 2023-05-15T03:30:00Z
+            start_time: Some(prost_types::Timestamp {
+                seconds: 1684121400,
+                nanos: 0,
+            }),
+            end_time: Some(prost_types::Timestamp {
+                seconds: 1684124700,
+                nanos: 0,
+            }),
+            faculty: "Dr. Sample".into(),
+            room: "E2-101".into(),
+            attendance: AttendanceState::Present as i32,
+        })
+        .unwrap();
+
+        assert_eq!(class.start.format("%H:%M").to_string(), "09:00");
+        assert_eq!(class.end.format("%H:%M").to_string(), "09:55");
+        assert_eq!(
+            class.start.date_naive(),
+            NaiveDate::from_ymd_opt(2023, 5, 15).unwrap()
+        );
+        assert_eq!(class.attendance, AttendanceState::Present);
+    }
+
+    #[test]
+    fn dates() {
+        let date = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        assert_eq!(NaiveDate::try_from(Date::from(date)), Ok(date));
+
+        assert!(NaiveDate::try_from(Date {
+            year: 2023,
+            month: 2,
+            day: 30
+        })
+        .is_err());
+    }
+}
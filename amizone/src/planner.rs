@@ -0,0 +1,237 @@
+//! Attendance planning, how many classes of each course can be missed without dropping below
+//! a target percentage, or how many have to be attended to get back above it.
+//!
+//! All of it assumes every upcoming class is either attended or missed in a row, which is the
+//! question students actually ask ("can I skip the next few?").
+
+use std::fmt::Display;
+
+use crate::model::{Attendance, AttendanceRecord, CourseRef};
+
+/// Attendance required, kept in basis points (hundredths of a percent) so the planning is
+/// exact integer math.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Target(u32);
+
+impl Target {
+    /// The usual requirement at Amity
+    pub const DEFAULT: Target = Target(7500);
+
+    /// `None` unless `percent` is above 0 and at most 100
+    pub fn from_percent(percent: f64) -> Option<Self> {
+        if percent.is_finite() && percent > 0.0 && percent <= 100.0 {
+            Some(Self((percent * 100.0).round() as u32).max(Self(1)))
+        } else {
+            None
+        }
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    fn basis_points(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.percent())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanStatus {
+    /// At or above the target, and stays there after missing this many classes in a row
+    CanMiss(u32),
+    /// Below the target, gets back to it after attending this many classes in a row
+    MustAttend(u32),
+    /// Below a 100% target after missing even one class, nothing gets it back
+    Unreachable,
+}
+
+impl PlanStatus {
+    pub fn is_on_track(&self) -> bool {
+        matches!(self, PlanStatus::CanMiss(_))
+    }
+}
+
+impl Attendance {
+    pub fn plan(&self, target: Target) -> PlanStatus {
+        const WHOLE: u64 = 10_000;
+
+        let attended = self.attended as u64 * WHOLE;
+        let required = target.basis_points() * self.held as u64;
+
+        if attended >= required {
+            // Largest k with attended / (held + k) >= target
+            let most_held = attended / target.basis_points();
+            let can_miss = most_held.saturating_sub(self.held as u64);
+            return PlanStatus::CanMiss(can_miss.min(u32::MAX as u64) as u32);
+        }
+
+        // Every class attended only closes the gap by (1 - target)
+        let gain = WHOLE - target.basis_points();
+        if gain == 0 {
+            return PlanStatus::Unreachable;
+        }
+
+        let deficit = required - attended;
+        let must_attend = (deficit + gain - 1) / gain;
+        PlanStatus::MustAttend(must_attend.min(u32::MAX as u64) as u32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoursePlan {
+    pub course: CourseRef,
+    pub attendance: Attendance,
+    pub status: PlanStatus,
+}
+
+/// Plans for every course in a semester
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemesterPlan {
+    pub target: Target,
+    pub courses: Vec<CoursePlan>,
+}
+
+impl SemesterPlan {
+    pub fn new(records: &[AttendanceRecord], target: Target) -> Self {
+        Self {
+            target,
+            courses: records
+                .iter()
+                .map(|record| CoursePlan {
+                    course: record.course.clone(),
+                    attendance: record.attendance,
+                    status: record.attendance.plan(target),
+                })
+                .collect(),
+        }
+    }
+
+    /// Attendance across every course put together
+    pub fn overall(&self) -> Attendance {
+        self.courses
+            .iter()
+            .fold(Attendance::default(), |total, course| Attendance {
+                attended: total.attended + course.attendance.attended,
+                held: total.held + course.attendance.held,
+            })
+    }
+
+    /// Courses below the target
+    pub fn at_risk(&self) -> impl Iterator<Item = &CoursePlan> {
+        self.courses
+            .iter()
+            .filter(|course| !course.status.is_on_track())
+    }
+
+    /// Classes that have to be attended, summed over every course below the target
+    pub fn must_attend(&self) -> u32 {
+        self.courses
+            .iter()
+            .map(|course| match course.status {
+                PlanStatus::MustAttend(classes) => classes,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attendance(attended: u32, held: u32) -> Attendance {
+        Attendance { attended, held }
+    }
+
+    #[test]
+    fn targets() {
+        assert_eq!(Target::from_percent(75.0), Some(Target::DEFAULT));
+        assert_eq!(Target::from_percent(100.0).unwrap().percent(), 100.0);
+        assert_eq!(Target::from_percent(0.0), None);
+        assert_eq!(Target::from_percent(100.5), None);
+        assert_eq!(Target::from_percent(f64::NAN), None);
+    }
+
+    #[test]
+    fn can_miss() {
+        // 30/40, 30/41 is below 75%
+        assert_eq!(
+            attendance(30, 40).plan(Target::DEFAULT),
+            PlanStatus::CanMiss(0)
+        );
+        // 36/40, 36/48 is exactly 75%
+        assert_eq!(
+            attendance(36, 40).plan(Target::DEFAULT),
+            PlanStatus::CanMiss(8)
+        );
+        assert_eq!(
+            attendance(0, 0).plan(Target::DEFAULT),
+            PlanStatus::CanMiss(0)
+        );
+        assert_eq!(
+            attendance(1, 1).plan(Target::DEFAULT),
+            PlanStatus::CanMiss(0)
+        );
+        assert_eq!(
+            attendance(3, 3).plan(Target::DEFAULT),
+            PlanStatus::CanMiss(1)
+        );
+    }
+
+    #[test]
+    fn must_attend() {
+        // 29/40, 32/43 is 74.4% and 33/44 is 75%
+        assert_eq!(
+            attendance(29, 40).plan(Target::DEFAULT),
+            PlanStatus::MustAttend(4)
+        );
+        assert_eq!(
+            attendance(0, 1).plan(Target::DEFAULT),
+            PlanStatus::MustAttend(3)
+        );
+
+        let target = Target::from_percent(85.0).unwrap();
+        // 22/26 is 84.6% and 23/27 is 85.2%
+        assert_eq!(attendance(16, 20).plan(target), PlanStatus::MustAttend(7));
+    }
+
+    #[test]
+    fn perfect_attendance() {
+        let target = Target::from_percent(100.0).unwrap();
+
+        assert_eq!(attendance(10, 10).plan(target), PlanStatus::CanMiss(0));
+        assert_eq!(attendance(9, 10).plan(target), PlanStatus::Unreachable);
+    }
+
+    #[test]
+    fn semester() {
+        let record = |code: &str, attended, held| AttendanceRecord {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            attendance: attendance(attended, held),
+        };
+
+        let plan = SemesterPlan::new(
+            &[record("A", 36, 40), record("B", 29, 40), record("C", 0, 1)],
+            Target::DEFAULT,
+        );
+
+        assert_eq!(plan.overall(), attendance(65, 81));
+        assert_eq!(plan.at_risk().count(), 2);
+        assert_eq!(plan.must_attend(), 7);
+    }
+}
@@ -0,0 +1,579 @@
+//! An in-process fake of go-amizone, for exercising [`UserClient`](crate::api::client::UserClient)
+//! and [`new_amizone_connection`](crate::api::new_amizone_connection) end to end without the network.
+//!
+//! Only available with the `test-support` feature, which is also what makes `build.rs` generate
+//! the server stubs.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE},
+    Engine as _,
+};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::api::types::{go_amizone::server::proto::v1 as goamizone, *};
+use goamizone::amizone_service_server::{AmizoneService, AmizoneServiceServer};
+
+pub static SAMPLE_USERNAME: &str = "sampleuser";
+pub static SAMPLE_PASSWORD: &str = "samplepass";
+
+/// The only day [`FakeAmizone::with_sample_data`] has classes scheduled on, 15th May 2023.
+pub static SAMPLE_DATE: (i32, i32, i32) = (2023, 5, 15);
+
+/// 2023-05-15T00:00:00Z
+const SAMPLE_DATE_EPOCH: i64 = 1684108800;
+
+type RpcResult<T> = std::result::Result<Response<T>, Status>;
+
+/// Everything the fake serves, can be modified while the server is running through
+/// [`FakeAmizone::state`].
+#[derive(Default, Clone)]
+pub struct FakeState {
+    /// username -> password, any other credentials are rejected with `Unauthenticated`
+    pub accounts: HashMap<String, String>,
+    pub attendance: Vec<AttendanceRecord>,
+    /// (year, month, day) -> classes
+    pub schedules: HashMap<(i32, i32, i32), Vec<ScheduledClass>>,
+    pub exam_title: String,
+    pub exams: Vec<ScheduledExam>,
+    pub semesters: Vec<Semester>,
+    pub current_semester: String,
+    /// semester ref -> courses
+    pub courses: HashMap<String, Vec<Course>>,
+    /// semester ref -> results
+    pub exam_results: HashMap<String, ExamResultRecords>,
+    pub profile: AmizoneProfile,
+    pub wifi: WifiMacInfo,
+    /// How many faculties the next feedback submission will be filled for
+    pub pending_feedback: i32,
+    /// RPC name -> number of authenticated calls made to it
+    pub calls: HashMap<&'static str, usize>,
+    /// RPC name -> number of requests that reached the fake, including failed ones
+    pub attempts: HashMap<&'static str, usize>,
+    /// Errors the next requests fail with before anything else is checked, one per request
+    pub failures: VecDeque<Status>,
+    /// How long every RPC takes to answer, go-amizone scrapes amizone on every request so
+    /// real responses are anything but instant
+    pub latency: Duration,
+}
+
+#[derive(Default, Clone)]
+pub struct FakeAmizone {
+    state: Arc<Mutex<FakeState>>,
+}
+
+/// Handle to a [`FakeAmizone`] listening on loopback, shuts the server down when dropped.
+pub struct FakeServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: JoinHandle<()>,
+}
+
+impl FakeAmizone {
+    /// An empty fake with no accounts, every RPC fails with `Unauthenticated` until one is added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fake with one account ([`SAMPLE_USERNAME`], [`SAMPLE_PASSWORD`]) and some canned
+    /// attendance, classes on [`SAMPLE_DATE`], a datesheet, courses, results, a profile and
+    /// WiFi MAC state.
+    pub fn with_sample_data() -> Self {
+        let fake = Self::new().with_account(SAMPLE_USERNAME, SAMPLE_PASSWORD);
+
+        {
+            let mut state = fake.state();
+
+            let courses = [
+                ("CSE101", "Programming in C", 30, 28),
+                ("MAT102", "Applied Mathematics", 32, 24),
+                ("PHY103", "Engineering Physics", 20, 14),
+            ];
+
+            state.attendance = courses
+                .iter()
+                .map(|(code, name, held, attended)| AttendanceRecord {
+                    attendance: Some(goamizone::Attendance {
+                        attended: *attended,
+                        held: *held,
+                    }),
+                    course: Some(course_ref(code, name)),
+                })
+                .collect();
+
+            state.schedules.insert(
+                SAMPLE_DATE,
+                courses
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (code, name, _, _))| {
+                        // Classes at 09:15, 10:15 and 11:15 IST, an hour long each
+                        let start = SAMPLE_DATE_EPOCH + (3 * 60 + 45) * 60 + i as i64 * 60 * 60;
+                        ScheduledClass {
+                            course: Some(course_ref(code, name)),
+                            start_time: Some(timestamp(start)),
+                            end_time: Some(timestamp(start + 60 * 60)),
+                            faculty: format!("Faculty {}", i + 1),
+                            room: format!("E-{}0{}", i + 2, i + 1),
+                            attendance: AttendanceState::Pending as i32,
+                        }
+                    })
+                    .collect(),
+            );
+
+            state.exam_title = String::from("Major Examination May 2023");
+            state.exams = courses
+                .iter()
+                .enumerate()
+                .map(|(i, (code, name, _, _))| ScheduledExam {
+                    course: Some(course_ref(code, name)),
+                    // 10:00 IST, a week apart starting a week after SAMPLE_DATE
+                    time: Some(timestamp(
+                        SAMPLE_DATE_EPOCH + (4 * 60 + 30) * 60 + (i as i64 + 1) * 7 * 24 * 60 * 60,
+                    )),
+                    mode: String::from("Offline"),
+                    location: Some(format!("Exam Hall {}", i + 1)),
+                })
+                .collect();
+
+            state.semesters = (1..=2)
+                .map(|num| Semester {
+                    name: format!("Semester {}", num),
+                    r#ref: num.to_string(),
+                })
+                .collect();
+            state.current_semester = String::from("2");
+
+            state.courses.insert(
+                String::from("2"),
+                courses
+                    .iter()
+                    .map(|(code, name, held, attended)| Course {
+                        r#ref: Some(course_ref(code, name)),
+                        r#type: String::from("Compulsory"),
+                        attendance: Some(goamizone::Attendance {
+                            attended: *attended,
+                            held: *held,
+                        }),
+                        internal_marks: Some(goamizone::Marks {
+                            have: 24.0,
+                            max: 30.0,
+                        }),
+                        syllabus_doc: String::new(),
+                    })
+                    .collect(),
+            );
+
+            state.exam_results.insert(
+                String::from("1"),
+                ExamResultRecords {
+                    course_wise: [("CSE001", "Basic Electronics", "A+", 9, 4)]
+                        .iter()
+                        .map(
+                            |(code, name, grade, grade_point, credits)| ExamResultRecord {
+                                course: Some(course_ref(code, name)),
+                                score: Some(goamizone::Score {
+                                    max: 100,
+                                    grade: grade.to_string(),
+                                    grade_point: *grade_point,
+                                }),
+                                credits: Some(goamizone::Credits {
+                                    acquired: *credits,
+                                    effective: *credits,
+                                    points: grade_point * credits,
+                                }),
+                                publish_date: Some(Date {
+                                    year: 2023,
+                                    month: 1,
+                                    day: 20,
+                                }),
+                            },
+                        )
+                        .collect(),
+                    overall: vec![OverallResult {
+                        semester: Some(goamizone::SemesterRef {
+                            semester_ref: String::from("1"),
+                        }),
+                        semester_grade_point_average: 9.0,
+                        cumulative_grade_point_average: 9.0,
+                    }],
+                },
+            );
+
+            state.profile = AmizoneProfile {
+                name: String::from("Sample User"),
+                enrollment_number: String::from("A2305222000"),
+                enrollment_validity: Some(timestamp(1811980800)),
+                batch: String::from("2022-2026"),
+                program: String::from("B.Tech (CSE)"),
+                date_of_birth: Some(timestamp(1041379200)),
+                blood_group: String::from("O+"),
+                id_card_number: String::from("123456"),
+                uuid: String::from("00000000-0000-0000-0000-000000000000"),
+            };
+
+            state.wifi = WifiMacInfo {
+                addresses: vec![String::from("00:11:22:33:44:55")],
+                slots: 2,
+                free_slots: 1,
+            };
+
+            state.pending_feedback = 3;
+        }
+
+        fake
+    }
+
+    pub fn with_account(self, username: impl ToString, password: impl ToString) -> Self {
+        self.state()
+            .accounts
+            .insert(username.to_string(), password.to_string());
+        self
+    }
+
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.state().latency = latency;
+        self
+    }
+
+    /// Make the next `times` requests, to any RPC, fail with `status`
+    pub fn fail_next(&self, status: Status, times: usize) {
+        self.state()
+            .failures
+            .extend(std::iter::repeat(status).take(times));
+    }
+
+    /// Lock the state to inspect or script it, don't hold the guard across an `.await`.
+    pub fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Number of authenticated calls made to an RPC, by its proto name (eg. `GetAttendance`)
+    pub fn calls(&self, rpc: &str) -> usize {
+        self.state().calls.get(rpc).copied().unwrap_or_default()
+    }
+
+    /// Number of requests made to an RPC, whether they succeeded or not
+    pub fn attempts(&self, rpc: &str) -> usize {
+        self.state().attempts.get(rpc).copied().unwrap_or_default()
+    }
+
+    /// Serve this fake over plaintext on a random loopback port.
+    pub async fn serve(&self) -> std::io::Result<FakeServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (shutdown, signal) = oneshot::channel();
+
+        let service = AmizoneServiceServer::new(self.clone());
+        let handle = tokio::spawn(async move {
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                    signal.await.ok();
+                })
+                .await
+                .expect("Fake amizone server crashed");
+        });
+
+        Ok(FakeServer {
+            addr,
+            shutdown: Some(shutdown),
+            handle,
+        })
+    }
+
+    /// Fails the request if a failure was scripted, otherwise authenticates it and then waits
+    /// out the configured latency.
+    async fn admit<M>(&self, rpc: &'static str, request: &Request<M>) -> Result<()> {
+        {
+            let mut state = self.state();
+            *state.attempts.entry(rpc).or_default() += 1;
+            if let Some(status) = state.failures.pop_front() {
+                return Err(status);
+            }
+        }
+
+        self.authenticate(rpc, request)?;
+
+        let latency = self.state().latency;
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the `authorization` metadata set by `UserClient::prepare_request` against
+    /// the known accounts, and counts the call if it passes.
+    fn authenticate<M>(&self, rpc: &'static str, request: &Request<M>) -> Result<()> {
+        let header = request
+            .metadata()
+            .get("authorization")
+            .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))?
+            .to_str()
+            .map_err(|_| Status::unauthenticated("Malformed authorization metadata"))?;
+
+        let encoded = header
+            .strip_prefix("Basic ")
+            .ok_or_else(|| Status::unauthenticated("Expected basic auth"))?;
+
+        let decoded = URL_SAFE
+            .decode(encoded)
+            .or_else(|_| STANDARD.decode(encoded))
+            .map_err(|_| Status::unauthenticated("Malformed basic auth"))?;
+        let decoded = String::from_utf8(decoded)
+            .map_err(|_| Status::unauthenticated("Malformed basic auth"))?;
+
+        let (username, password) = decoded
+            .split_once(':')
+            .ok_or_else(|| Status::unauthenticated("Malformed basic auth"))?;
+
+        let mut state = self.state();
+        match state.accounts.get(username) {
+            Some(expected) if expected == password => {
+                *state.calls.entry(rpc).or_default() += 1;
+                Ok(())
+            }
+            _ => Err(Status::unauthenticated("Invalid credentials")),
+        }
+    }
+}
+
+impl FakeServer {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Plaintext url to hand to [`new_amizone_connection`](crate::api::new_amizone_connection)
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+        self.handle.abort();
+    }
+}
+
+#[tonic::async_trait]
+impl AmizoneService for FakeAmizone {
+    async fn get_attendance(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<goamizone::AttendanceRecords> {
+        self.admit("GetAttendance", &request).await?;
+
+        Ok(Response::new(goamizone::AttendanceRecords {
+            records: self.state().attendance.clone(),
+        }))
+    }
+
+    async fn get_class_schedule(
+        &self,
+        request: Request<goamizone::ClassScheduleRequest>,
+    ) -> RpcResult<goamizone::ScheduledClasses> {
+        self.admit("GetClassSchedule", &request).await?;
+
+        let date = request
+            .into_inner()
+            .date
+            .ok_or_else(|| Status::invalid_argument("Missing date"))?;
+
+        let classes = self
+            .state()
+            .schedules
+            .get(&(date.year, date.month, date.day))
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(Response::new(goamizone::ScheduledClasses { classes }))
+    }
+
+    async fn get_exam_schedule(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<goamizone::ExaminationSchedule> {
+        self.admit("GetExamSchedule", &request).await?;
+
+        let state = self.state();
+        Ok(Response::new(goamizone::ExaminationSchedule {
+            title: state.exam_title.clone(),
+            exams: state.exams.clone(),
+        }))
+    }
+
+    async fn get_semesters(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<goamizone::SemesterList> {
+        self.admit("GetSemesters", &request).await?;
+
+        Ok(Response::new(goamizone::SemesterList {
+            semesters: self.state().semesters.clone(),
+        }))
+    }
+
+    async fn get_courses(
+        &self,
+        request: Request<goamizone::SemesterRef>,
+    ) -> RpcResult<goamizone::Courses> {
+        self.admit("GetCourses", &request).await?;
+
+        let semester = request.into_inner().semester_ref;
+        match self.state().courses.get(&semester) {
+            Some(courses) => Ok(Response::new(goamizone::Courses {
+                courses: courses.clone(),
+            })),
+            None => Err(Status::not_found("No such semester")),
+        }
+    }
+
+    async fn get_current_courses(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<goamizone::Courses> {
+        self.admit("GetCurrentCourses", &request).await?;
+
+        let state = self.state();
+        Ok(Response::new(goamizone::Courses {
+            courses: state
+                .courses
+                .get(&state.current_semester)
+                .cloned()
+                .unwrap_or_default(),
+        }))
+    }
+
+    async fn get_exam_result(
+        &self,
+        request: Request<goamizone::SemesterRef>,
+    ) -> RpcResult<ExamResultRecords> {
+        self.admit("GetExamResult", &request).await?;
+
+        let semester = request.into_inner().semester_ref;
+        Ok(Response::new(
+            self.state()
+                .exam_results
+                .get(&semester)
+                .cloned()
+                .unwrap_or_default(),
+        ))
+    }
+
+    async fn get_current_exam_result(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<ExamResultRecords> {
+        self.admit("GetCurrentExamResult", &request).await?;
+
+        let state = self.state();
+        Ok(Response::new(
+            state
+                .exam_results
+                .get(&state.current_semester)
+                .cloned()
+                .unwrap_or_default(),
+        ))
+    }
+
+    async fn get_user_profile(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<AmizoneProfile> {
+        self.admit("GetUserProfile", &request).await?;
+
+        Ok(Response::new(self.state().profile.clone()))
+    }
+
+    async fn get_wifi_mac_info(
+        &self,
+        request: Request<goamizone::EmptyMessage>,
+    ) -> RpcResult<WifiMacInfo> {
+        self.admit("GetWifiMacInfo", &request).await?;
+
+        Ok(Response::new(self.state().wifi.clone()))
+    }
+
+    async fn register_wifi_mac(
+        &self,
+        request: Request<goamizone::RegisterWifiMacRequest>,
+    ) -> RpcResult<goamizone::EmptyMessage> {
+        self.admit("RegisterWifiMac", &request).await?;
+
+        let request = request.into_inner();
+        let mut state = self.state();
+
+        if state.wifi.addresses.contains(&request.address) {
+            return Err(Status::already_exists("Address already registered"));
+        }
+        if state.wifi.free_slots <= 0 && !request.override_limit {
+            return Err(Status::failed_precondition("No free slots left"));
+        }
+
+        state.wifi.addresses.push(request.address);
+        state.wifi.free_slots = (state.wifi.free_slots - 1).max(0);
+
+        Ok(Response::new(goamizone::EmptyMessage {}))
+    }
+
+    async fn deregister_wifi_mac(
+        &self,
+        request: Request<goamizone::DeregisterWifiMacRequest>,
+    ) -> RpcResult<goamizone::EmptyMessage> {
+        self.admit("DeregisterWifiMac", &request).await?;
+
+        let address = request.into_inner().address;
+        let mut state = self.state();
+
+        match state.wifi.addresses.iter().position(|a| a == &address) {
+            Some(index) => {
+                state.wifi.addresses.remove(index);
+                state.wifi.free_slots = (state.wifi.free_slots + 1).min(state.wifi.slots);
+                Ok(Response::new(goamizone::EmptyMessage {}))
+            }
+            None => Err(Status::not_found("Address not registered")),
+        }
+    }
+
+    async fn fill_faculty_feedback(
+        &self,
+        request: Request<goamizone::FillFacultyFeedbackRequest>,
+    ) -> RpcResult<goamizone::FillFacultyFeedbackResponse> {
+        self.admit("FillFacultyFeedback", &request).await?;
+
+        let request = request.into_inner();
+        if !(1..=5).contains(&request.rating) || !(1..=3).contains(&request.query_rating) {
+            return Err(Status::invalid_argument("Rating out of range"));
+        }
+
+        let mut state = self.state();
+        let filled_for = std::mem::take(&mut state.pending_feedback);
+
+        Ok(Response::new(goamizone::FillFacultyFeedbackResponse {
+            filled_for,
+        }))
+    }
+}
+
+fn course_ref(code: &str, name: &str) -> goamizone::CourseRef {
+    goamizone::CourseRef {
+        code: code.to_string(),
+        name: name.to_string(),
+    }
+}
+
+fn timestamp(seconds: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp { seconds, nanos: 0 }
+}
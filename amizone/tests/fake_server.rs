@@ -0,0 +1,378 @@
+#![cfg(feature = "test-support")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use amizone::api::{
+    backend::AmizoneBackend,
+    cache::{CacheConfig, CachingBackend, ResponseCache},
+    client::UserClient,
+    connection::{RetryPolicy, Rpc},
+    new_amizone_connection,
+    types::{AmizoneApiError, AttendanceState, Date, ScheduledClass, StatusCode},
+    user::credentials::Credentials,
+    ConnectionConfig, ConnectionPolicy,
+};
+use amizone::model::{self, ClassSlot, Profile};
+use amizone::testing::{FakeAmizone, FakeServer, SAMPLE_DATE, SAMPLE_PASSWORD, SAMPLE_USERNAME};
+use chrono::{Datelike, NaiveDate};
+
+/// Retries without the waiting, so failing tests fail fast
+fn test_policy() -> ConnectionPolicy {
+    ConnectionPolicy {
+        retry: RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        },
+        ..Default::default()
+    }
+}
+
+/// The server shuts down when dropped, so keep it around for as long as the client is used
+async fn client_for(
+    fake: &FakeAmizone,
+    username: &str,
+    password: &str,
+) -> (FakeServer, UserClient) {
+    client_with_policy(fake, username, password, test_policy()).await
+}
+
+async fn client_with_policy(
+    fake: &FakeAmizone,
+    username: &str,
+    password: &str,
+    policy: ConnectionPolicy,
+) -> (FakeServer, UserClient) {
+    let server = fake.serve().await.unwrap();
+    let connection = new_amizone_connection(ConnectionConfig {
+        policy,
+        ..ConnectionConfig::new(server.url())
+    })
+    .unwrap();
+    let client = UserClient::new(
+        Credentials::new(username, password).get_auth().unwrap(),
+        connection,
+    );
+
+    (server, client)
+}
+
+#[tokio::test]
+async fn authenticated_requests() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    let attendance = client.get_attendance().await.unwrap();
+    assert_eq!(attendance.len(), 3);
+
+    let profile = client.get_user_profile().await.unwrap();
+    assert_eq!(profile.name, "Sample User");
+
+    let (year, month, day) = SAMPLE_DATE;
+    let classes = client
+        .get_class_schedule(Date { year, month, day })
+        .await
+        .unwrap();
+    assert_eq!(classes.len(), 3);
+
+    let holiday = client
+        .get_class_schedule(Date {
+            year,
+            month,
+            day: day + 1,
+        })
+        .await
+        .unwrap();
+    assert!(holiday.is_empty());
+
+    assert_eq!(fake.calls("GetAttendance"), 1);
+    assert_eq!(fake.calls("GetClassSchedule"), 2);
+}
+
+#[tokio::test]
+async fn rejects_bad_credentials() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, "wrongpass").await;
+
+    let err = client.get_attendance().await.unwrap_err();
+    assert_eq!(err.code(), StatusCode::Unauthenticated);
+    assert_eq!(fake.calls("GetAttendance"), 0);
+}
+
+#[tokio::test]
+async fn wifi_mac_state() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    client.register_wifi_mac("AA:BB:CC:DD:EE:FF").await.unwrap();
+    let info = client.get_wifi_mac_info().await.unwrap();
+    assert!(info.addresses.contains(&String::from("AA:BB:CC:DD:EE:FF")));
+    assert_eq!(info.free_slots, 0);
+
+    client
+        .deregister_wifi_mac("AA:BB:CC:DD:EE:FF")
+        .await
+        .unwrap();
+    let err = client
+        .deregister_wifi_mac("AA:BB:CC:DD:EE:FF")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), StatusCode::NotFound);
+
+    assert_eq!(
+        client.fill_faculty_feedback(5, 3, "Nice.").await.unwrap(),
+        3
+    );
+    assert_eq!(
+        client.fill_faculty_feedback(5, 3, "Nice.").await.unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn retries_idempotent_reads() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    fake.fail_next(AmizoneApiError::unavailable("go-amizone restarting"), 2);
+    assert_eq!(client.get_attendance().await.unwrap().len(), 3);
+    assert_eq!(fake.attempts("GetAttendance"), 3);
+
+    fake.fail_next(AmizoneApiError::unavailable("go-amizone down"), 10);
+    let err = client.get_semesters().await.unwrap_err();
+    assert_eq!(err.code(), StatusCode::Unavailable);
+    assert_eq!(fake.attempts("GetSemesters"), 4);
+
+    // Not transient, retrying wouldn't help
+    fake.state().failures.clear();
+    fake.fail_next(AmizoneApiError::internal("amizone changed its html"), 1);
+    client.get_user_profile().await.unwrap_err();
+    assert_eq!(fake.attempts("GetUserProfile"), 1);
+}
+
+#[tokio::test]
+async fn never_retries_mutations() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    fake.fail_next(AmizoneApiError::unavailable("go-amizone restarting"), 1);
+    let err = client
+        .register_wifi_mac("AA:BB:CC:DD:EE:FF")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), StatusCode::Unavailable);
+    assert_eq!(fake.attempts("RegisterWifiMac"), 1);
+
+    fake.fail_next(AmizoneApiError::unavailable("go-amizone restarting"), 1);
+    client
+        .fill_faculty_feedback(5, 3, "Nice.")
+        .await
+        .unwrap_err();
+    assert_eq!(fake.attempts("FillFacultyFeedback"), 1);
+}
+
+#[tokio::test]
+async fn per_rpc_deadlines() {
+    let fake = FakeAmizone::with_sample_data().with_latency(Duration::from_millis(200));
+    let mut policy = test_policy();
+    policy.retry.max_retries = 0;
+    policy
+        .deadlines
+        .set(Rpc::GetAttendance, Duration::from_millis(50));
+    let (_server, client) =
+        client_with_policy(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD, policy).await;
+
+    // The server gets the deadline too, whichever side gives up first decides the code
+    let err = client.get_attendance().await.unwrap_err();
+    assert!(matches!(
+        err.code(),
+        StatusCode::DeadlineExceeded | StatusCode::Cancelled
+    ));
+
+    assert_eq!(client.get_user_profile().await.unwrap().name, "Sample User");
+}
+
+#[tokio::test]
+async fn connects_lazily() {
+    // Nothing listens on the discard port, connecting only fails once a request is made
+    let connection = new_amizone_connection(ConnectionConfig {
+        policy: test_policy(),
+        ..ConnectionConfig::new("http://127.0.0.1:9")
+    })
+    .unwrap();
+    let client = UserClient::new(
+        Credentials::new(SAMPLE_USERNAME, SAMPLE_PASSWORD)
+            .get_auth()
+            .unwrap(),
+        connection,
+    );
+
+    let err = client.get_attendance().await.unwrap_err();
+    assert_eq!(err.code(), StatusCode::Unavailable);
+}
+
+#[tokio::test]
+async fn caches_reads_per_user() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+    let cache = Arc::new(ResponseCache::new(CacheConfig::default()));
+    let cached = CachingBackend::new(client.clone(), "1", cache.clone());
+    let other = CachingBackend::new(client, "2", cache.clone());
+
+    cached.get_user_profile().await.unwrap();
+    cached.get_user_profile().await.unwrap();
+    assert_eq!(fake.calls("GetUserProfile"), 1);
+
+    // Never shared between users
+    other.get_user_profile().await.unwrap();
+    assert_eq!(fake.calls("GetUserProfile"), 2);
+
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+
+    // Errors aren't cached
+    fake.fail_next(AmizoneApiError::internal("amizone changed its html"), 1);
+    cached.get_attendance().await.unwrap_err();
+    assert_eq!(cached.get_attendance().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn late_marks_arent_cached_away() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+    let mut config = CacheConfig::default();
+    config
+        .set_ttl(Rpc::GetClassSchedule, Duration::from_millis(50))
+        .unwrap();
+    let cached = CachingBackend::new(client, "1", Arc::new(ResponseCache::new(config)));
+
+    let yesterday = model::today().pred_opt().unwrap();
+    let before = yesterday.pred_opt().unwrap();
+    {
+        let mut state = fake.state();
+        let classes = state.schedules[&SAMPLE_DATE].clone();
+        for date in [yesterday, before] {
+            let day = (date.year(), date.month() as i32, date.day() as i32);
+            state.schedules.insert(day, classes.clone());
+        }
+    }
+
+    let states = |classes: Vec<ScheduledClass>| {
+        classes
+            .into_iter()
+            .map(|class| class.attendance)
+            .collect::<Vec<_>>()
+    };
+    let pending = vec![AttendanceState::Pending as i32; 3];
+
+    for date in [yesterday, before] {
+        assert_eq!(
+            states(cached.get_class_schedule(date.into()).await.unwrap()),
+            pending
+        );
+    }
+
+    for classes in fake.state().schedules.values_mut() {
+        classes[0].attendance = AttendanceState::Absent as i32;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Yesterday's classes can still be marked, only older days are kept as they were
+    let marked = states(cached.get_class_schedule(yesterday.into()).await.unwrap());
+    assert_eq!(marked[0], AttendanceState::Absent as i32);
+    assert_eq!(
+        states(cached.get_class_schedule(before.into()).await.unwrap()),
+        pending
+    );
+}
+
+#[tokio::test]
+async fn mutations_invalidate_the_cache() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+    let cached = CachingBackend::new(
+        client,
+        "1",
+        Arc::new(ResponseCache::new(CacheConfig::default())),
+    );
+
+    let before = cached.get_wifi_mac_info().await.unwrap();
+    cached.register_wifi_mac("AA:BB:CC:DD:EE:FF").await.unwrap();
+    let after = cached.get_wifi_mac_info().await.unwrap();
+
+    assert!(!before
+        .addresses
+        .contains(&String::from("AA:BB:CC:DD:EE:FF")));
+    assert!(after.addresses.contains(&String::from("AA:BB:CC:DD:EE:FF")));
+    assert_eq!(fake.calls("GetWifiMacInfo"), 2);
+}
+
+#[tokio::test]
+async fn converts_to_model() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    let profile = Profile::try_from(client.get_user_profile().await.unwrap()).unwrap();
+    assert_eq!(profile.name, "Sample User");
+
+    let (year, month, day) = SAMPLE_DATE;
+    let classes = client
+        .get_class_schedule(Date { year, month, day })
+        .await
+        .unwrap()
+        .into_iter()
+        .map(ClassSlot::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(classes
+        .iter()
+        .all(|class| class.start < class.end && class.start.offset() == &model::ist()));
+}
+
+#[tokio::test]
+async fn schedule_range() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    let (year, month, day) = SAMPLE_DATE;
+    let sample = NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap();
+    let week = client
+        .get_schedule_range(
+            sample - chrono::Duration::days(3),
+            sample + chrono::Duration::days(3),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(week.len(), 7);
+    assert_eq!(week[&sample].len(), 3);
+    assert_eq!(
+        week.values().filter(|classes| classes.is_empty()).count(),
+        6
+    );
+    assert_eq!(fake.calls("GetClassSchedule"), 7);
+
+    let err = client
+        .get_schedule_range(sample, sample - chrono::Duration::days(1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), StatusCode::InvalidArgument);
+}
+
+#[tokio::test]
+async fn exam_results() {
+    let fake = FakeAmizone::with_sample_data();
+    let (_server, client) = client_for(&fake, SAMPLE_USERNAME, SAMPLE_PASSWORD).await;
+
+    let results = client.get_exam_results(&[1, 2]).await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[&1].as_ref().unwrap().course_wise.len(), 1);
+    assert!(results[&2].as_ref().unwrap().course_wise.is_empty());
+    assert_eq!(fake.calls("GetExamResult"), 2);
+
+    // One semester failing doesn't fail the other
+    fake.fail_next(AmizoneApiError::internal("amizone changed its html"), 1);
+    let results = client.get_exam_results(&[1, 2]).await;
+    assert_eq!(results.values().filter(|result| result.is_err()).count(), 1);
+}
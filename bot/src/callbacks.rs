@@ -1,4 +1,7 @@
 use super::error::BotError;
+use super::jobs;
+use super::scheduler::{JobContext, Scheduler, SchedulerConfig};
+use super::server::{self, FeedConfig, FeedState};
 use super::{Connections, Context, Data, Result, IGNORE_CHECK};
 use log::{debug, error, info, trace};
 use std::collections::HashMap;
@@ -8,7 +11,12 @@ use std::sync::Arc;
 use std::time;
 use tokio::sync::Mutex;
 
-use amizone::api::{self as amizoneapi, client::UserClient, types::AmizoneApiError};
+use amizone::api::{
+    self as amizoneapi,
+    cache::{CacheConfig, CachingBackend, ResponseCache},
+    types::{AmizoneApiError, AmizoneClient, AmizoneConnection},
+    user::User,
+};
 use poise::{
     serenity_prelude::{Context as SerenityContext, Ready, UserId},
     Framework,
@@ -44,16 +52,46 @@ pub async fn on_ready<'a>(
     trace!("Setting up connections");
     let connections = Connections {
         amizone: amizoneapi::new_amizone_connection(
-            env::var("AMIZONE_API_URL").unwrap_or("https://amizone.fly.dev".to_string()),
+            amizoneapi::ConnectionConfig::from_env().expect("Invalid go-amizone connection config"),
         )
-        .await
-        .expect("Failed to initialize connection to go-amizone."),
+        .expect("Invalid go-amizone connection config"),
         db: amizoneapi::new_db_connection(
             env::var("DATABASE_URL").expect("missing `DATABASE_URL`"),
         )
         .await
-        .expect("Failed to init connection to go-amizone"),
+        .expect("Failed to init connection to the database"),
     };
+    let response_cache = CacheConfig::from_env()
+        .expect("Invalid response cache config")
+        .map(|config| Arc::new(ResponseCache::new(config)));
+
+    let feed = FeedConfig::from_env().expect("Invalid calendar feed config");
+    if let Some(ref config) = feed {
+        trace!("Starting the calendar feed server");
+        tokio::spawn(server::serve(
+            config.clone(),
+            FeedState {
+                amizone: connections.amizone.clone(),
+                db: connections.db.clone(),
+                response_cache: response_cache.clone(),
+            },
+        ));
+    }
+
+    trace!("Starting the job scheduler");
+    let mut scheduler = Scheduler::new(
+        connections.db.clone(),
+        SchedulerConfig::from_env().expect("Invalid job scheduler config"),
+    );
+    jobs::register(&mut scheduler);
+    let scheduler = Arc::new(scheduler);
+    scheduler.start(JobContext {
+        http: ctx.http.clone(),
+        db: connections.db.clone(),
+        amizone: connections.amizone.clone(),
+        response_cache: response_cache.clone(),
+    });
+
     let start_time = time::Instant::now();
     let dev_user_id = UserId::from_str(&env::var("DEV_ID").unwrap_or_default()).unwrap_or_default();
 
@@ -65,6 +103,9 @@ pub async fn on_ready<'a>(
         colourscheme: crate::ColourScheme::amity_colours(),
         bot_user_id: ready.user.id,
         users_cache: Arc::new(Mutex::new(HashMap::new())),
+        response_cache,
+        feed,
+        scheduler,
     })
 }
 
@@ -133,10 +174,14 @@ pub async fn init_client(ctx: Context<'_>) {
             let amizone_conn = &ctx.data().connections.amizone;
             let caller_id = ctx.author().id.to_string();
 
-            match amizoneapi::user::User::from_id(caller_id, db_client).await {
+            match User::from_id(caller_id, db_client).await {
                 Ok(user) => match user {
-                    Some(user) => match user.get_client(amizone_conn.clone()) {
-                        Ok(user_client) => {
+                    Some(user) => match user_client(
+                        &user,
+                        amizone_conn,
+                        ctx.data().response_cache.as_ref(),
+                    ) {
+                        Ok(client) => {
                             trace!(
                                 "User {} is logged in, pre_command succeeded.",
                                 ctx.author().id
@@ -147,13 +192,13 @@ pub async fn init_client(ctx: Context<'_>) {
                                     .users_cache
                                     .lock()
                                     .await
-                                    .insert(ctx.author().id, user_client.clone());
+                                    .insert(ctx.author().id, client.clone());
                             }
-                            Ok(user_client)
+                            Ok(client)
                         }
-                        Err(amizone_error) => {
+                        Err(err) => {
                             debug!("Error in retrieving the client for {}", ctx.author().id);
-                            Err(amizone_error.into())
+                            Err(err)
                         }
                     },
                     None => {
@@ -167,16 +212,30 @@ pub async fn init_client(ctx: Context<'_>) {
             }
         };
 
-    ctx.set_invocation_data::<Result<UserClient>>(invocation_data)
+    ctx.set_invocation_data::<Result<AmizoneClient>>(invocation_data)
         .await;
 }
 
+/// go-amizone client of a stored user, behind the response cache if there is one
+pub fn user_client(
+    user: &User,
+    connection: &AmizoneConnection,
+    cache: Option<&Arc<ResponseCache>>,
+) -> Result<AmizoneClient> {
+    let client = user.get_client(connection.clone())?;
+
+    Ok(match cache {
+        Some(cache) => Arc::new(CachingBackend::new(client, user.id(), cache.clone())),
+        None => Arc::new(client),
+    })
+}
+
 pub async fn loggedin_check(ctx: Context<'_>) -> Result<bool> {
     if IGNORE_CHECK.contains(&ctx.invoked_command_name()) {
         return Ok(true);
     }
 
-    if amizoneapi::user::User::from_id(ctx.author().id.to_string(), &ctx.data().connections.db)
+    if User::from_id(ctx.author().id.to_string(), &ctx.data().connections.db)
         .await?
         .is_none()
     {
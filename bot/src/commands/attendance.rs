@@ -1,4 +1,5 @@
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::model::AttendanceRecord;
 
 use crate::{CommandResult, Context, Result};
 
@@ -14,46 +15,39 @@ Note: This command requires you to be logged in using the /login command. If you
 #[poise::command(prefix_command, slash_command, help_text_fn = "attendance_help")]
 pub async fn attendance(ctx: Context<'_>) -> CommandResult {
     ctx.defer_ephemeral().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
-    let records = client.get_attendance().await?;
+    let records = client
+        .get_attendance()
+        .await?
+        .into_iter()
+        .map(AttendanceRecord::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
 
     let mut message = String::from("```");
     // let mut message = String::new;
 
-    for record in records {
-        let (code, name) = match &record.course {
-            Some(course) => (course.code.as_str(), course.name.as_str()),
-            _ => ("", ""),
+    for AttendanceRecord { course, attendance } in records {
+        let (percentage_str, emoji) = match attendance.percentage() {
+            Some(percentage) if percentage >= 85.0 => (format!("{:.2}%", percentage), "👍"),
+            Some(percentage) if percentage >= 75.0 => (format!("{:.2}%", percentage), "🚨"),
+            Some(percentage) => (format!("{:.2}%", percentage), "👎"),
+            None => (String::from("N/A"), "❔"),
         };
 
-        let (attended, held) = match &record.attendance {
-            Some(attendance) => (attendance.attended, attendance.held),
-            _ => (-1, -1),
-        };
-
-        let percentage = (attended as f64 / held as f64) * 100.0;
-        let percentage_str = format!("{:.2}%", percentage);
-
-        let emoji;
-        if percentage >= 85.0 {
-            emoji = "👍";
-        } else if percentage >= 75.0 {
-            emoji = "🚨";
-        } else {
-            emoji = "👎";
-        }
-
         // message.push_str(&format!("📚 **{} ({})**\n", name, code));
-        message.push_str(&format!("📚 {} ({})\n", name, code));
-        message.push_str(&format!("✅ Attended: {}\n", attended));
+        message.push_str(&format!("📚 {}\n", course));
+        message.push_str(&format!("✅ Attended: {}\n", attendance.attended));
         // message.push_str(&format!(
         //     "📅 {}% ({}/{}) {}\n\n",
         //     percentage_str, attended, held, emoji
         // ));
-        message.push_str(&format!("📅 Held: {}\n", held));
+        message.push_str(&format!("📅 Held: {}\n", attendance.held));
         message.push_str(&format!("{} Percentage: {}\n\n", emoji, percentage_str));
     }
 
@@ -0,0 +1,41 @@
+use crate::{CommandResult, Context};
+use mute::mute;
+use set::set;
+use stop::stop;
+use unmute::unmute;
+
+mod mute;
+mod set;
+mod stop;
+mod unmute;
+
+static ATTENDANCEALERTS_HELP: &str = "/attendancealerts [subcommands] - Commands to get a DM when your attendance gets low.\n\n\
+Example:\n\
+/attendancealerts set\n\
+/attendancealerts set 80 10\n\
+/attendancealerts mute CSE101\n\
+/attendancealerts unmute CSE101\n\
+/attendancealerts stop\n\n\
+Note: Your attendance is checked every couple of hours. You get a DM when a course gets close to your target, \
+and another one when it drops below it. A course has to get back clearly above a line before you are alerted about it again.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancealerts_help",
+    aliases("attalerts"),
+    subcommands("set", "mute", "unmute", "stop")
+)]
+/// Commands to get a DM when your attendance gets low.
+pub async fn attendancealerts(ctx: Context<'_>) -> CommandResult {
+    ctx.say(ATTENDANCEALERTS_HELP).await?;
+    Ok(())
+}
+
+fn attendancealerts_help() -> String {
+    ATTENDANCEALERTS_HELP.into()
+}
+
+/// Reply to a user without alerts
+static NO_ALERTS: &str =
+    "You don't get attendance alerts, set them up with `/attendancealerts set`.";
@@ -0,0 +1,52 @@
+use super::NO_ALERTS;
+use crate::jobs::attendance_alerts;
+use crate::{CommandResult, Context};
+
+static ATTENDANCEALERTSMUTE_HELP: &str =
+    "/attendancealerts mute - Stop attendance alerts about a course.\n\n\
+Usage: /attendancealerts mute <course>\n\n\
+Arguments:\n\
+- <course>: Code of the course, as shown by `/attendance`.\n\n\
+Example:\n\
+/attendancealerts mute CSE101";
+
+/// Stop attendance alerts about a course.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancealertsmute_help"
+)]
+pub async fn mute(
+    ctx: Context<'_>,
+    #[description = "Code of the course, eg. CSE101"] course: String,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let settings = attendance_alerts::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let Some(mut alerts) = settings.get(&key).await? else {
+        ctx.say(NO_ALERTS).await?;
+        return Ok(());
+    };
+
+    let course = course.trim().to_uppercase();
+    if alerts.is_muted(&course) {
+        ctx.say(format!("`{}` is already muted.", course)).await?;
+        return Ok(());
+    }
+
+    alerts.muted.push(course.clone());
+    settings.put(&key, &alerts).await?;
+
+    ctx.say(format!(
+        "Muted `{}`, unmute it with `/attendancealerts unmute`.",
+        course
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn attendancealertsmute_help() -> String {
+    ATTENDANCEALERTSMUTE_HELP.into()
+}
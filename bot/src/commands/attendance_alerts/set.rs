@@ -0,0 +1,77 @@
+use crate::jobs::attendance_alerts::{
+    self, AttendanceAlerts, DEFAULT_BAND, KIND, MAX_BAND, SCHEDULE,
+};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+use amizone::planner::Target;
+
+static ATTENDANCEALERTSSET_HELP: &str =
+    "/attendancealerts set - Get a DM when your attendance gets low.\n\n\
+Usage: /attendancealerts set [threshold] [band]\n\n\
+Arguments:\n\
+- [threshold]: Optional attendance percentage to stay above, defaults to 75.\n\
+- [band]: Optional percentage points above the threshold that get a warning, from 0 to 25. \
+If not provided, courses under 5 points above the threshold get a warning.\n\n\
+Example:\n\
+/attendancealerts set\n\
+/attendancealerts set 80 10\n\n\
+Note: Courses already low are alerted about on the first check. Setting it again keeps your muted courses.";
+
+/// Get a DM when your attendance gets low.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancealertsset_help"
+)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Attendance percentage to stay above, defaults to 75"] threshold: Option<f64>,
+    #[description = "Points above it that get a warning, defaults to 5"] band: Option<f64>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let target = match threshold {
+        Some(percent) => match Target::from_percent(percent) {
+            Some(target) => target,
+            None => {
+                ctx.say("Threshold should be a percentage above 0 and at most 100.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => Target::default(),
+    };
+
+    let band = band.unwrap_or(DEFAULT_BAND);
+    if !(0.0..=MAX_BAND).contains(&band) {
+        ctx.say(format!("Band should be from 0 to {} points.", MAX_BAND))
+            .await?;
+        return Ok(());
+    }
+
+    let settings = attendance_alerts::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let mut alerts = AttendanceAlerts::new(target.percent(), band);
+    if let Some(previous) = settings.get(&key).await? {
+        alerts.muted = previous.muted;
+    }
+    settings.put(&key, &alerts).await?;
+
+    ctx.data()
+        .scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say(format!(
+        "You'll get a DM when a course drops below **{}**, or gets under **{:.2}%**.",
+        target,
+        target.percent() + band
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn attendancealertsset_help() -> String {
+    ATTENDANCEALERTSSET_HELP.into()
+}
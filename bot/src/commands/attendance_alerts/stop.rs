@@ -0,0 +1,37 @@
+use super::NO_ALERTS;
+use crate::jobs::attendance_alerts::{self, KIND};
+use crate::{CommandResult, Context};
+
+static ATTENDANCEALERTSSTOP_HELP: &str =
+    "/attendancealerts stop - Stop getting attendance alerts.\n\n\
+Usage: /attendancealerts stop\n\n\
+Example:\n\
+/attendancealerts stop\n\n\
+Note: Your threshold and muted courses are forgotten.";
+
+/// Stop getting attendance alerts.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancealertsstop_help"
+)]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    attendance_alerts::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Attendance alerts stopped.").await?;
+    } else {
+        ctx.say(NO_ALERTS).await?;
+    }
+
+    Ok(())
+}
+
+fn attendancealertsstop_help() -> String {
+    ATTENDANCEALERTSSTOP_HELP.into()
+}
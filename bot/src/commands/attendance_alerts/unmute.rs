@@ -0,0 +1,50 @@
+use super::NO_ALERTS;
+use crate::jobs::attendance_alerts;
+use crate::{CommandResult, Context};
+
+static ATTENDANCEALERTSUNMUTE_HELP: &str =
+    "/attendancealerts unmute - Get attendance alerts about a muted course again.\n\n\
+Usage: /attendancealerts unmute <course>\n\n\
+Arguments:\n\
+- <course>: Code of the course.\n\n\
+Example:\n\
+/attendancealerts unmute CSE101";
+
+/// Get attendance alerts about a muted course again.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancealertsunmute_help"
+)]
+pub async fn unmute(
+    ctx: Context<'_>,
+    #[description = "Code of the course, eg. CSE101"] course: String,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let settings = attendance_alerts::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let Some(mut alerts) = settings.get(&key).await? else {
+        ctx.say(NO_ALERTS).await?;
+        return Ok(());
+    };
+
+    let course = course.trim();
+    if !alerts.is_muted(course) {
+        ctx.say(format!("`{}` isn't muted.", course)).await?;
+        return Ok(());
+    }
+
+    alerts
+        .muted
+        .retain(|muted| !muted.eq_ignore_ascii_case(course));
+    settings.put(&key, &alerts).await?;
+
+    ctx.say(format!("Unmuted `{}`.", course)).await?;
+
+    Ok(())
+}
+
+fn attendancealertsunmute_help() -> String {
+    ATTENDANCEALERTSUNMUTE_HELP.into()
+}
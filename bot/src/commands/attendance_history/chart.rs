@@ -0,0 +1,203 @@
+use std::io::Cursor;
+use std::sync::Once;
+
+use super::NO_HISTORY;
+use crate::error::BotError;
+use crate::jobs::history;
+use crate::{CommandResult, Context, Result};
+use amizone::planner::Target;
+use chrono::{Duration, NaiveDate};
+use image::{ImageOutputFormat, RgbImage};
+use log::error;
+use plotters::prelude::*;
+use plotters::style::register_font;
+use poise::serenity_prelude::AttachmentType;
+
+static ATTENDANCEHISTORYCHART_HELP: &str =
+    "/attendancehistory chart - Chart how your attendance moved over the semester.\n\n\
+Usage: /attendancehistory chart [course] [target]\n\n\
+Arguments:\n\
+- [course]: Optional course code. If provided, only that course is charted. \
+If not provided, every course is.\n\
+- [target]: Optional attendance percentage to mark on the chart, defaults to 75.\n\n\
+Example:\n\
+/attendancehistory chart\n\
+/attendancehistory chart CSE101\n\
+/attendancehistory chart CSE101 80\n\n\
+Note: There's one point a day from when you started recording, days the bot couldn't reach Amizone are skipped.";
+
+/// Drawn with a bundled font, the bot may run where there aren't any to find
+static FONT: &[u8] = include_bytes!("../../../assets/DejaVuSans.ttf");
+static REGISTER_FONT: Once = Once::new();
+
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 600;
+const FILENAME: &str = "attendance.png";
+
+/// Chart how your attendance moved over the semester.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancehistorychart_help"
+)]
+pub async fn chart(
+    ctx: Context<'_>,
+    #[description = "Course code, defaults to every course"] course: Option<String>,
+    #[description = "Attendance percentage to mark, defaults to 75"] target: Option<f64>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let target = match target {
+        Some(percent) => match Target::from_percent(percent) {
+            Some(target) => target,
+            None => {
+                ctx.say("Target should be a percentage above 0 and at most 100.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => Target::default(),
+    };
+
+    let Some(attendance_history) = history::settings(&ctx.data().connections.db)
+        .get(&ctx.author().id.to_string())
+        .await?
+    else {
+        ctx.say(NO_HISTORY).await?;
+        return Ok(());
+    };
+
+    let courses = attendance_history
+        .courses()
+        .iter()
+        .filter(|(code, _)| {
+            course
+                .as_ref()
+                .map_or(true, |course| course.eq_ignore_ascii_case(code))
+        })
+        .map(|(code, history)| (code.clone(), history.percentages()))
+        .collect::<Vec<_>>();
+
+    if courses.is_empty() {
+        ctx.say("No such course in your attendance.").await?;
+        return Ok(());
+    }
+    // Days no class of the courses was held on have no percentage to chart
+    if attendance_history.days() < 2 || courses.iter().all(|(_, points)| points.is_empty()) {
+        ctx.say("There's only today's attendance so far, check back in a few days.")
+            .await?;
+        return Ok(());
+    }
+
+    // Drawing and encoding take a while, they're kept off the async workers
+    let png = tokio::task::spawn_blocking(move || draw(&courses, target))
+        .await
+        .map_err(draw_error)??;
+    let colour = ctx.data().colourscheme.primary;
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.color(colour)
+                .title("Attendance history")
+                .description(format!(
+                    "Attendance recorded on **{}** day(s), **{}** is marked in black.",
+                    attendance_history.days(),
+                    target
+                ))
+                .image(format!("attachment://{}", FILENAME))
+        })
+        .attachment(AttachmentType::Bytes {
+            data: png.into(),
+            filename: String::from(FILENAME),
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn attendancehistorychart_help() -> String {
+    ATTENDANCEHISTORYCHART_HELP.into()
+}
+
+/// Draws a line of every course's attendance percentages by day, and one at `target`, into a PNG
+fn draw(series: &[(String, Vec<(NaiveDate, f64)>)], target: Target) -> Result<Vec<u8>> {
+    REGISTER_FONT.call_once(|| {
+        if register_font("sans-serif", FontStyle::Normal, FONT).is_err() {
+            error!("The bundled chart font couldn't be loaded");
+        }
+    });
+
+    let dates = series.iter().flat_map(|(_, points)| points.iter());
+    let first = dates.clone().map(|(date, _)| *date).min();
+    let last = dates.map(|(date, _)| *date).max();
+    let (Some(first), Some(last)) = (first, last) else {
+        return Err("No attendance to chart".into());
+    };
+    // The range can't be empty, a course with a single day of history still gets a chart
+    let last = last.max(first + Duration::days(1));
+
+    let mut buffer = vec![0; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(draw_error)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Attendance", ("sans-serif", 28))
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(first..last, 0.0..100.0)
+            .map_err(draw_error)?;
+
+        chart
+            .configure_mesh()
+            .x_labels(8)
+            .x_label_formatter(&|date: &NaiveDate| date.format("%d %b").to_string())
+            .y_label_formatter(&|percent: &f64| format!("{:.0}%", percent))
+            .draw()
+            .map_err(draw_error)?;
+
+        let line = BLACK.stroke_width(2);
+        chart
+            .draw_series(LineSeries::new(
+                [(first, target.percent()), (last, target.percent())],
+                line,
+            ))
+            .map_err(draw_error)?
+            .label(format!("Target {}", target))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], line));
+
+        for (index, (code, points)) in series.iter().enumerate() {
+            let line = Palette99::pick(index).to_rgba().stroke_width(2);
+            chart
+                .draw_series(LineSeries::new(points.iter().copied(), line).point_size(3))
+                .map_err(draw_error)?
+                .label(code.as_str())
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], line));
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(draw_error)?;
+
+        root.present().map_err(draw_error)?;
+    }
+
+    let image =
+        RgbImage::from_raw(WIDTH, HEIGHT, buffer).ok_or("Chart buffer is of the wrong size")?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|err| format!("Couldn't encode the chart: {}", err))?;
+
+    Ok(png.into_inner())
+}
+
+fn draw_error(err: impl std::fmt::Display) -> BotError {
+    format!("Couldn't draw the chart: {}", err).into()
+}
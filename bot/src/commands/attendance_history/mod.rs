@@ -0,0 +1,38 @@
+use crate::{CommandResult, Context};
+use chart::chart;
+use start::start;
+use stop::stop;
+
+mod chart;
+mod start;
+mod stop;
+
+static ATTENDANCEHISTORY_HELP: &str = "/attendancehistory [subcommands] - Commands to keep track of how your attendance moves over the semester.\n\n\
+Example:\n\
+/attendancehistory start\n\
+/attendancehistory chart\n\
+/attendancehistory chart CSE101 80\n\
+/attendancehistory stop\n\n\
+Note: Your attendance is recorded a few times a day once you start, and the last one of every day is kept. \
+The chart shows the percentage of every course over time, with the target marked.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancehistory_help",
+    aliases("atthistory", "trend"),
+    subcommands("start", "chart", "stop")
+)]
+/// Commands to keep track of how your attendance moves over the semester.
+pub async fn attendancehistory(ctx: Context<'_>) -> CommandResult {
+    ctx.say(ATTENDANCEHISTORY_HELP).await?;
+    Ok(())
+}
+
+fn attendancehistory_help() -> String {
+    ATTENDANCEHISTORY_HELP.into()
+}
+
+/// Reply to a user without history
+static NO_HISTORY: &str =
+    "Your attendance isn't being recorded, start with `/attendancehistory start`.";
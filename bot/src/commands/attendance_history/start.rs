@@ -0,0 +1,55 @@
+use crate::jobs::history::{self, AttendanceHistory, KIND, SCHEDULE};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context, Result};
+use amizone::api::types::AmizoneClient;
+
+static ATTENDANCEHISTORYSTART_HELP: &str =
+    "/attendancehistory start - Start recording your attendance.\n\n\
+Usage: /attendancehistory start\n\n\
+Example:\n\
+/attendancehistory start\n\n\
+Note: Amizone only has your attendance as of now, so the history starts today.";
+
+/// Start recording your attendance.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancehistorystart_help"
+)]
+pub async fn start(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let scheduler = &ctx.data().scheduler;
+    if scheduler.get(ctx.author().id, KIND).await?.is_some() {
+        ctx.say("Your attendance is already being recorded.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    // Today's attendance is recorded right away, the job only runs some hours from now
+    let mut attendance_history = AttendanceHistory::default();
+    history::snapshot(client, &mut attendance_history).await?;
+
+    history::settings(&ctx.data().connections.db)
+        .put(&ctx.author().id.to_string(), &attendance_history)
+        .await?;
+    scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say("Your attendance will be recorded every day, see how it moves with `/attendancehistory chart` in a few days.")
+        .await?;
+
+    Ok(())
+}
+
+fn attendancehistorystart_help() -> String {
+    ATTENDANCEHISTORYSTART_HELP.into()
+}
@@ -0,0 +1,37 @@
+use super::NO_HISTORY;
+use crate::jobs::history::{self, KIND};
+use crate::{CommandResult, Context};
+
+static ATTENDANCEHISTORYSTOP_HELP: &str =
+    "/attendancehistory stop - Stop recording your attendance.\n\n\
+Usage: /attendancehistory stop\n\n\
+Example:\n\
+/attendancehistory stop\n\n\
+Note: The attendance recorded so far is forgotten.";
+
+/// Stop recording your attendance.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancehistorystop_help"
+)]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    history::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Stopped recording your attendance.").await?;
+    } else {
+        ctx.say(NO_HISTORY).await?;
+    }
+
+    Ok(())
+}
+
+fn attendancehistorystop_help() -> String {
+    ATTENDANCEHISTORYSTOP_HELP.into()
+}
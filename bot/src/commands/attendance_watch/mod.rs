@@ -0,0 +1,31 @@
+use crate::{CommandResult, Context};
+use start::start;
+use stop::stop;
+
+mod start;
+mod stop;
+
+static ATTENDANCEWATCH_HELP: &str = "/attendancewatch [subcommands] - Commands to get a DM when your attendance for a class is marked.\n\n\
+Example:\n\
+/attendancewatch start\n\
+/attendancewatch start true\n\
+/attendancewatch stop\n\n\
+Note: Today's classes are checked every 20 minutes. The DM has the course, faculty and time of the class, \
+so a wrong absent can be disputed the same day.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancewatch_help",
+    aliases("watch"),
+    subcommands("start", "stop")
+)]
+/// Commands to get a DM when your attendance for a class is marked.
+pub async fn attendancewatch(ctx: Context<'_>) -> CommandResult {
+    ctx.say(ATTENDANCEWATCH_HELP).await?;
+    Ok(())
+}
+
+fn attendancewatch_help() -> String {
+    ATTENDANCEWATCH_HELP.into()
+}
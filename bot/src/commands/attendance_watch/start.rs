@@ -0,0 +1,58 @@
+use crate::jobs::attendance_watch::{self, AttendanceWatch, KIND, SCHEDULE};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+
+static ATTENDANCEWATCHSTART_HELP: &str =
+    "/attendancewatch start - Get a DM when your attendance for a class is marked.\n\n\
+Usage: /attendancewatch start [absent_only]\n\n\
+Arguments:\n\
+- [absent_only]: Optional, `true` to only get a DM when you are marked absent. \
+If not provided, you get one for presents too.\n\n\
+Example:\n\
+/attendancewatch start\n\
+/attendancewatch start true\n\n\
+Note: Classes already marked when you start aren't sent.";
+
+/// Get a DM when your attendance for a class is marked.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancewatchstart_help"
+)]
+pub async fn start(
+    ctx: Context<'_>,
+    #[description = "Only DM when marked absent, defaults to false"] absent_only: Option<bool>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let absent_only = absent_only.unwrap_or(false);
+    let settings = attendance_watch::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let watch = match settings.get(&key).await? {
+        Some(watch) => AttendanceWatch {
+            absent_only,
+            ..watch
+        },
+        None => AttendanceWatch::new(absent_only),
+    };
+    settings.put(&key, &watch).await?;
+
+    ctx.data()
+        .scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    if absent_only {
+        ctx.say("You'll get a DM when you are marked absent in a class.")
+            .await?;
+    } else {
+        ctx.say("You'll get a DM when your attendance for a class is marked.")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn attendancewatchstart_help() -> String {
+    ATTENDANCEWATCHSTART_HELP.into()
+}
@@ -0,0 +1,35 @@
+use crate::jobs::attendance_watch::{self, KIND};
+use crate::{CommandResult, Context};
+
+static ATTENDANCEWATCHSTOP_HELP: &str =
+    "/attendancewatch stop - Stop getting a DM when your attendance is marked.\n\n\
+Usage: /attendancewatch stop\n\n\
+Example:\n\
+/attendancewatch stop";
+
+/// Stop getting a DM when your attendance is marked.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "attendancewatchstop_help"
+)]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    attendance_watch::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Attendance watch stopped.").await?;
+    } else {
+        ctx.say("You aren't watching your attendance.").await?;
+    }
+
+    Ok(())
+}
+
+fn attendancewatchstop_help() -> String {
+    ATTENDANCEWATCHSTOP_HELP.into()
+}
@@ -1,4 +1,4 @@
-use amizone::api::user::User;
+use amizone::api::{backend::AmizoneBackend, user::User};
 
 use crate::{BotError, CommandResult, Context};
 
@@ -32,7 +32,7 @@ pub async fn login(
     let amizone_conn = &ctx.data().connections.amizone;
     let caller_id = ctx.author().id.to_string();
 
-    let mut amizone_client = User::new(&caller_id, &username, &password, db_client)
+    let amizone_client = User::new(&caller_id, &username, &password, db_client)
         .await?
         .get_client(amizone_conn.clone())?;
 
@@ -26,7 +26,12 @@ pub async fn logout(ctx: Context<'_>) -> CommandResult {
     let db_client = &ctx.data().connections.db;
     let caller_id = ctx.author().id.to_string();
 
-    User::forget(caller_id, db_client).await?;
+    User::forget(&caller_id, db_client).await?;
+    ctx.data().scheduler.remove_user(ctx.author().id).await?;
+    crate::jobs::forget_user(db_client, ctx.author().id).await?;
+    if let Some(ref cache) = ctx.data().response_cache {
+        cache.invalidate_user(&caller_id);
+    }
 
     ctx.say("Logged out successfully.").await?;
 
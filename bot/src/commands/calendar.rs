@@ -0,0 +1,97 @@
+use crate::{CommandResult, Context, Result};
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::ics::Calendar;
+use amizone::model::{self, ClassSlot, Exam};
+use chrono::{Duration, NaiveDate};
+use poise::serenity_prelude::AttachmentType;
+
+static CALENDAR_HELP: &str =
+    "/calendar - Export your classes and exams as a calendar file (.ics).\n\n\
+Usage: /calendar [weeks]\n\n\
+Aliases: ics, export\n\n\
+Arguments:\n\
+- [weeks]: Optional number of weeks of classes to export starting today, from 1 to 8. \
+If not provided, it exports the next 2 weeks.\n\n\
+Example:\n\
+/calendar\n\
+/calendar 4\n\n\
+Note: The file can be imported into Google Calendar, Outlook, Apple Calendar and most other calendar apps. \
+Importing a newer export updates the classes and exams already imported instead of adding them again. \
+Exams are assumed to take 3 hours.";
+
+/// Export your classes and exams as a calendar file
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "calendar_help",
+    aliases("ics", "export")
+)]
+pub async fn calendar(
+    ctx: Context<'_>,
+    #[description = "Weeks of classes to export starting today, defaults to 2"]
+    #[min = 1]
+    #[max = 8]
+    weeks: Option<i64>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    let weeks = weeks.unwrap_or(2).clamp(1, 8);
+    let start = model::today();
+    let end = start + Duration::weeks(weeks) - Duration::days(1);
+
+    let calendar = build_calendar(&**client, start, end).await?;
+
+    if calendar.is_empty() {
+        ctx.say("No classes or exams to export.").await?;
+        return Ok(());
+    }
+
+    let data = calendar.to_string().into_bytes();
+
+    ctx.send(|b| {
+        b.content(format!(
+            "**{}** classes and exams from the next {} week(s), open the file with your calendar app to import them.",
+            calendar.len(),
+            weeks
+        ))
+        .attachment(AttachmentType::Bytes {
+            data: data.into(),
+            filename: String::from("amizone.ics"),
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Classes from `start` to `end` (inclusive) and every exam on the datesheet
+pub(crate) async fn build_calendar(
+    client: &dyn AmizoneBackend,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Calendar> {
+    let mut calendar = Calendar::new("Amizone");
+
+    for (_, classes) in client.get_schedule_range(start, end).await? {
+        for class in classes {
+            calendar.add_class(&ClassSlot::try_from(class)?);
+        }
+    }
+
+    let (_, datesheet) = client.get_exam_schedule().await?;
+    for exam in datesheet {
+        calendar.add_exam(&Exam::try_from(exam)?);
+    }
+
+    Ok(calendar)
+}
+
+fn calendar_help() -> String {
+    CALENDAR_HELP.into()
+}
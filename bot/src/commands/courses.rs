@@ -2,8 +2,9 @@ use std::ops::Deref;
 
 use crate::util;
 use crate::{CommandResult, Context, Result};
-use amizone::api::client::UserClient;
-use amizone::api::types::Course;
+use amizone::api::backend::AmizoneBackend;
+use amizone::api::types::AmizoneClient;
+use amizone::model::Course;
 use poise::serenity_prelude::CreateEmbed;
 
 static COURSES_HELP: &str ="/courses - Retrieve and select your courses.\n\n\
@@ -25,7 +26,10 @@ pub async fn courses(
     #[description = "Semester number"] semester: Option<usize>,
 ) -> CommandResult {
     ctx.defer_ephemeral().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
     let courses = match semester {
@@ -33,15 +37,12 @@ pub async fn courses(
         None => client.get_current_courses().await?,
     }
     .into_iter()
-    .map(|course| course.into())
-    .collect::<Vec<AmizoneCourse>>();
+    .map(|course| Course::try_from(course).map(AmizoneCourse::from))
+    .collect::<std::result::Result<Vec<AmizoneCourse>, _>>()?;
 
     let options = courses
         .iter()
-        .map(|course| match course.r#ref {
-            Some(ref course_ref) => course_ref.name.as_str(),
-            None => "",
-        })
+        .map(|course| course.course.name.as_str())
         .collect::<Vec<&str>>();
 
     util::make_select_menu(ctx, courses.as_slice(), options.as_slice(), "Select Course").await?;
@@ -74,41 +75,32 @@ impl From<Course> for AmizoneCourse {
 
 impl From<&AmizoneCourse> for CreateEmbed {
     fn from(value: &AmizoneCourse) -> Self {
-        let (attended, held) = match &value.attendance {
-            Some(attendance) => (attendance.attended, attendance.held),
-            _ => (-1, -1),
+        let attendance = match value.attendance {
+            Some(attendance) => match attendance.percentage() {
+                Some(percentage) => format!(
+                    "`{}` / **{}** = *{:.2}%*",
+                    attendance.attended, attendance.held, percentage
+                ),
+                None => String::from("No classes held yet"),
+            },
+            None => String::from("Not available"),
         };
 
-        let syllabus = &value.syllabus_doc;
-        let (obtained_marks, max_marks) = match value.internal_marks {
-            Some(ref marks) => (marks.have, marks.max),
-            None => (-1f32, -1f32),
+        let internal_marks = match value.internal_marks {
+            Some(marks) => format!("`{}` / **{}**", marks.have, marks.max),
+            None => String::from("Not out yet"),
         };
 
-        let course_type = &value.r#type;
+        let mut embed = CreateEmbed::default();
+        embed.title(format!("{} **{}**", value.course.name, value.course.code));
+        if let Some(ref syllabus) = value.syllabus {
+            embed.url(syllabus);
+        }
 
-        let (code, name) = match value.r#ref {
-            Some(ref course_ref) => (course_ref.code.as_str(), course_ref.name.as_str()),
-            None => ("", ""),
-        };
-
-        let percentage = (attended as f64 / held as f64) * 100.0;
-        let percentage_str = format!("*{:.2}%*", percentage);
-
-        CreateEmbed::default()
-            .title(format!("{} **{}**", name, code))
-            .url(syllabus)
-            .field(
-                "Attendance",
-                format!("`{}` / **{}** = {}", attended, held, percentage_str),
-                true,
-            )
-            .field(
-                "Internal Marks",
-                format!("`{}` / **{}**", obtained_marks, max_marks),
-                false,
-            )
-            .field("Course Type", course_type, false)
+        embed
+            .field("Attendance", attendance, true)
+            .field("Internal Marks", internal_marks, false)
+            .field("Course Type", &value.kind, false)
             .to_owned()
     }
 }
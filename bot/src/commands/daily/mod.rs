@@ -0,0 +1,36 @@
+use crate::{CommandResult, Context};
+use pause::pause;
+use resume::resume;
+use set::set;
+use stop::stop;
+
+mod pause;
+mod resume;
+mod set;
+mod stop;
+
+static DAILY_HELP: &str = "/daily [subcommands] - Commands to get the day's classes in a DM every day.\n\n\
+Example:\n\
+/daily set 7:30\n\
+/daily pause\n\
+/daily resume\n\
+/daily stop\n\n\
+Note: The timetable is sent at the time you set, in your timezone (IST unless you pick another one). \
+Nothing is sent on days without classes.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "daily_help",
+    aliases("dailytt"),
+    subcommands("set", "pause", "resume", "stop")
+)]
+/// Commands to get the day's classes in a DM every day.
+pub async fn daily(ctx: Context<'_>) -> CommandResult {
+    ctx.say(DAILY_HELP).await?;
+    Ok(())
+}
+
+fn daily_help() -> String {
+    DAILY_HELP.into()
+}
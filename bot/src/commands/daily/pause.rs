@@ -0,0 +1,34 @@
+use crate::jobs::daily::KIND;
+use crate::{CommandResult, Context};
+
+static DAILYPAUSE_HELP: &str = "/daily pause - Stop the daily timetable for a while.\n\n\
+Usage: /daily pause\n\n\
+Example:\n\
+/daily pause\n\n\
+Note: The time and timezone are kept, use `/daily resume` to get it again.";
+
+/// Stop the daily timetable for a while, keeping its time.
+#[poise::command(prefix_command, slash_command, help_text_fn = "dailypause_help")]
+pub async fn pause(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx
+        .data()
+        .scheduler
+        .set_paused(ctx.author().id, KIND, true)
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Daily timetable paused, resume it with `/daily resume`.")
+            .await?;
+    } else {
+        ctx.say("You don't get a daily timetable, set one up with `/daily set`.")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn dailypause_help() -> String {
+    DAILYPAUSE_HELP.into()
+}
@@ -0,0 +1,39 @@
+use crate::jobs::daily::KIND;
+use crate::{CommandResult, Context};
+
+static DAILYRESUME_HELP: &str = "/daily resume - Get the paused daily timetable again.\n\n\
+Usage: /daily resume\n\n\
+Example:\n\
+/daily resume";
+
+/// Get the paused daily timetable again.
+#[poise::command(prefix_command, slash_command, help_text_fn = "dailyresume_help")]
+pub async fn resume(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx
+        .data()
+        .scheduler
+        .set_paused(ctx.author().id, KIND, false)
+        .await?;
+
+    match job {
+        Some(job) => {
+            ctx.say(format!(
+                "Daily timetable resumed, the next one is <t:{}:R>.",
+                job.next_run.timestamp()
+            ))
+            .await?;
+        }
+        None => {
+            ctx.say("You don't get a daily timetable, set one up with `/daily set`.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn dailyresume_help() -> String {
+    DAILYRESUME_HELP.into()
+}
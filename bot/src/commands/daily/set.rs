@@ -0,0 +1,67 @@
+use crate::jobs::daily::KIND;
+use crate::scheduler::{Job, Schedule};
+use crate::util::{format_utc_offset, parse_time, parse_utc_offset};
+use crate::{CommandResult, Context};
+
+static DAILYSET_HELP: &str = "/daily set - Get the day's classes in a DM every day at a time.\n\n\
+Usage: /daily set <time> [timezone]\n\n\
+Arguments:\n\
+- <time>: Time of day to send the timetable at, eg. `7:30`, `19:00` or `7am`.\n\
+- [timezone]: Optional UTC offset of your timezone, eg. `+05:30`, `UTC-4` or `IST`. \
+If not provided, IST is used.\n\n\
+Example:\n\
+/daily set 7:30\n\
+/daily set 8am +01:00\n\n\
+Note: Setting it again replaces the old time, and resumes it if it was paused.";
+
+/// Get the day's classes in a DM every day at a time.
+#[poise::command(prefix_command, slash_command, help_text_fn = "dailyset_help")]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Time of day, eg. 7:30 or 7am"] time: String,
+    #[description = "Your UTC offset, defaults to IST"] timezone: Option<String>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let Some(at) = parse_time(&time) else {
+        ctx.say("Invalid time, use something like `7:30`, `19:00` or `7am`.")
+            .await?;
+        return Ok(());
+    };
+
+    let offset = match timezone {
+        Some(timezone) => match parse_utc_offset(&timezone) {
+            Some(offset) => offset,
+            None => {
+                ctx.say("Invalid timezone, use a UTC offset like `+05:30` or `UTC-4`.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => amizone::model::ist(),
+    };
+
+    let job = Job::new(
+        ctx.author().id,
+        KIND,
+        Schedule::Daily {
+            at,
+            offset_minutes: offset.local_minus_utc() / 60,
+        },
+    );
+    ctx.data().scheduler.schedule(&job).await?;
+
+    ctx.say(format!(
+        "You'll get the day's classes every day at `{}` (UTC{}), the first one <t:{}:R>.",
+        at.format("%H:%M"),
+        format_utc_offset(offset),
+        job.next_run.timestamp()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn dailyset_help() -> String {
+    DAILYSET_HELP.into()
+}
@@ -0,0 +1,28 @@
+use crate::jobs::daily::KIND;
+use crate::{CommandResult, Context};
+
+static DAILYSTOP_HELP: &str = "/daily stop - Stop getting the daily timetable.\n\n\
+Usage: /daily stop\n\n\
+Example:\n\
+/daily stop\n\n\
+Note: The time and timezone are forgotten, use `/daily pause` to keep them.";
+
+/// Stop getting the daily timetable.
+#[poise::command(prefix_command, slash_command, help_text_fn = "dailystop_help")]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+
+    if job.is_some() {
+        ctx.say("Daily timetable stopped.").await?;
+    } else {
+        ctx.say("You don't get a daily timetable.").await?;
+    }
+
+    Ok(())
+}
+
+fn dailystop_help() -> String {
+    DAILYSTOP_HELP.into()
+}
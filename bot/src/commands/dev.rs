@@ -0,0 +1,118 @@
+use amizone::api::user::User;
+use poise::serenity_prelude::UserId;
+
+use crate::{CommandResult, Context, Result};
+
+/// Re-encrypt every stored user with the active encryption key
+#[poise::command(prefix_command, hide_in_help, check = "dev_check")]
+pub async fn rotatekeys(ctx: Context<'_>) -> CommandResult {
+    let rotation = User::rotate_credentials(&ctx.data().connections.db).await?;
+
+    ctx.say(format!(
+        "Re-encrypted `{}` users with the active key, skipped `{}` whose credentials couldn't be decrypted.",
+        rotation.rotated, rotation.skipped
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Hit rate and size of the go-amizone response cache
+#[poise::command(prefix_command, hide_in_help, check = "dev_check")]
+pub async fn cachestats(ctx: Context<'_>) -> CommandResult {
+    let Some(ref cache) = ctx.data().response_cache else {
+        ctx.say("Response cache is disabled, set `AMIZONE_CACHE_SIZE` to enable it.")
+            .await?;
+        return Ok(());
+    };
+
+    let stats = cache.stats();
+    ctx.say(format!(
+        "**Hits:** `{}` | **Misses:** `{}` | **Hit rate:** `{:.1}%`\n\
+        **Entries:** `{}/{}` | **Evictions:** `{}`",
+        stats.hits,
+        stats.misses,
+        stats.hit_rate() * 100.0,
+        stats.entries,
+        cache.config().max_entries,
+        stats.evictions
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Background jobs, all of them or the ones of a user
+#[poise::command(prefix_command, hide_in_help, check = "dev_check")]
+pub async fn jobs(ctx: Context<'_>, user: Option<UserId>) -> CommandResult {
+    let scheduler = &ctx.data().scheduler;
+
+    if let Some(user) = user {
+        let jobs = scheduler.jobs_of(user).await?;
+        if jobs.is_empty() {
+            ctx.say(format!("No jobs for `{}`.", user)).await?;
+            return Ok(());
+        }
+
+        let mut message = String::new();
+        for job in jobs {
+            message.push_str(&format!(
+                "**{}**{} | Next: <t:{}:R> | Last: {} | Failures: `{}`\n",
+                job.kind,
+                if job.paused { " (paused)" } else { "" },
+                job.next_run.timestamp(),
+                job.last_run.map_or(String::from("never"), |last| format!(
+                    "<t:{}:R>",
+                    last.timestamp()
+                )),
+                job.failures
+            ));
+            if let Some(ref err) = job.last_error {
+                message.push_str(&format!("> {}\n", err));
+            }
+        }
+
+        ctx.say(message).await?;
+        return Ok(());
+    }
+
+    let status = scheduler.status().await?;
+    let config = scheduler.config();
+
+    let mut message = format!(
+        "**Jobs:** `{}` | **Paused:** `{}` | **Failing:** `{}`\n\
+        **Running:** `{}/{}` | **Runs:** `{}` | **Failed runs:** `{}`\n",
+        status.jobs,
+        status.paused,
+        status.failing,
+        status.running,
+        config.concurrency,
+        status.runs,
+        status.failures
+    );
+
+    for (kind, count) in status.by_kind.iter() {
+        message.push_str(&format!("- {}: `{}`\n", kind, count));
+    }
+
+    if !status.upcoming.is_empty() {
+        message.push_str("**Up next:**\n");
+        for job in status.upcoming.iter() {
+            message.push_str(&format!(
+                "- {} for `{}` <t:{}:R>\n",
+                job.kind,
+                job.user,
+                job.next_run.timestamp()
+            ));
+        }
+    }
+
+    ctx.say(message).await?;
+
+    Ok(())
+}
+
+/// Only lets the developer set in `DEV_ID` through
+async fn dev_check(ctx: Context<'_>) -> Result<bool> {
+    Ok(ctx.author().id == ctx.data().dev_user_id)
+}
@@ -1,4 +1,5 @@
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::model::Exam;
 
 use crate::{CommandResult, Context, Result};
 
@@ -16,33 +17,30 @@ If you don't have any upcoming exams, it will not return any information.";
 #[poise::command(prefix_command, slash_command, help_text_fn = "datesheet_help")]
 pub async fn datesheet(ctx: Context<'_>) -> CommandResult {
     ctx.defer().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
     let (title, datesheet) = client.get_exam_schedule().await?;
+    let datesheet = datesheet
+        .into_iter()
+        .map(Exam::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
 
     if !datesheet.is_empty() {
         let mut message = format!("**{}**```", title);
         // let mut message = String::new;
 
         for record in datesheet {
-            let (code, name) = match &record.course {
-                Some(course) => (course.code.as_str(), course.name.as_str()),
-                _ => ("", ""),
-            };
-
-            let time = record
-                .time
-                .unwrap_or_default()
-                .to_string()
-                .replace('T', " ")
-                .replace(":00Z", "");
-            let mode = record.mode;
-
-            message.push_str(&format!("📚 {} ({})\n", name, code));
-            message.push_str(&format!("📅 {}\n", time));
-            message.push_str(&format!("✍🏼 {}\n\n", mode));
+            message.push_str(&format!("📚 {}\n", record.course));
+            message.push_str(&format!("📅 {}\n", record.time.format("%Y-%m-%d %H:%M")));
+            if let Some(ref location) = record.location {
+                message.push_str(&format!("📍 {}\n", location));
+            }
+            message.push_str(&format!("✍🏼 {}\n\n", record.mode));
         }
 
         message.push_str("```");
@@ -0,0 +1,31 @@
+use crate::{CommandResult, Context};
+use set::set;
+use stop::stop;
+
+mod set;
+mod stop;
+
+static EXAMREMINDERS_HELP: &str = "/examreminders [subcommands] - Commands to get reminded of your exams.\n\n\
+Example:\n\
+/examreminders set\n\
+/examreminders set 3\n\
+/examreminders stop\n\n\
+Note: You get a DM with the whole datesheet when a new one comes out, then a reminder of every exam \
+the evening before and a few hours before it starts, with its mode and location.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "examreminders_help",
+    aliases("examremind"),
+    subcommands("set", "stop")
+)]
+/// Commands to get reminded of your exams.
+pub async fn examreminders(ctx: Context<'_>) -> CommandResult {
+    ctx.say(EXAMREMINDERS_HELP).await?;
+    Ok(())
+}
+
+fn examreminders_help() -> String {
+    EXAMREMINDERS_HELP.into()
+}
@@ -0,0 +1,62 @@
+use crate::jobs::exams::{self, ExamReminders, DEFAULT_HOURS, KIND, MAX_HOURS, SCHEDULE};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+
+static EXAMREMINDERSSET_HELP: &str = "/examreminders set - Get reminded of your exams.\n\n\
+Usage: /examreminders set [hours]\n\n\
+Arguments:\n\
+- [hours]: Optional number of hours before an exam to remind you, from 1 to 12. \
+If not provided, you are reminded 2 hours before.\n\n\
+Example:\n\
+/examreminders set\n\
+/examreminders set 3\n\n\
+Note: The evening reminder is always sent at 6 PM (IST) the day before the exam.";
+
+/// Get reminded of your exams.
+#[poise::command(prefix_command, slash_command, help_text_fn = "examremindersset_help")]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Hours before an exam, defaults to 2"]
+    #[min = 1]
+    #[max = 12]
+    hours: Option<u32>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let hours = hours.unwrap_or(DEFAULT_HOURS);
+    if !(1..=MAX_HOURS).contains(&hours) {
+        ctx.say(format!(
+            "Invalid number of hours, pick one from 1 to {}.",
+            MAX_HOURS
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // The datesheet summary and reminders already sent aren't sent again
+    let settings = exams::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let mut reminders = settings
+        .get(&key)
+        .await?
+        .unwrap_or_else(|| ExamReminders::new(hours));
+    reminders.hours = hours;
+    settings.put(&key, &reminders).await?;
+
+    ctx.data()
+        .scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say(format!(
+        "You'll be reminded of every exam the evening before and {} hour(s) before it starts.",
+        hours
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn examremindersset_help() -> String {
+    EXAMREMINDERSSET_HELP.into()
+}
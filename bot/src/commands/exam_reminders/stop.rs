@@ -0,0 +1,30 @@
+use crate::jobs::exams::{self, KIND};
+use crate::{CommandResult, Context};
+
+static EXAMREMINDERSSTOP_HELP: &str = "/examreminders stop - Stop getting exam reminders.\n\n\
+Usage: /examreminders stop\n\n\
+Example:\n\
+/examreminders stop";
+
+/// Stop getting exam reminders.
+#[poise::command(prefix_command, slash_command, help_text_fn = "examremindersstop_help")]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    exams::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Exam reminders stopped.").await?;
+    } else {
+        ctx.say("You don't get exam reminders.").await?;
+    }
+
+    Ok(())
+}
+
+fn examremindersstop_help() -> String {
+    EXAMREMINDERSSTOP_HELP.into()
+}
@@ -1,5 +1,5 @@
 use crate::{ApplicationContext, CommandResult, Result};
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
 use poise::modal::execute_modal;
 use poise::Modal;
 
@@ -72,12 +72,15 @@ pub async fn facultyfeedback(ctx: ApplicationContext<'_>) -> CommandResult {
     let msg = ctx.say("*Filling faculty feedback...*").await?;
 
     ctx.defer().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
     let filled = client
-        .fill_faculty_feedback(query, query_rating, feedback.comments)
+        .fill_faculty_feedback(query, query_rating, &feedback.comments)
         .await?;
 
     let reply = if filled > 0 {
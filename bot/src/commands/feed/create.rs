@@ -0,0 +1,42 @@
+use super::feed_config;
+use crate::{CommandResult, Context};
+use amizone::api::user::User;
+
+static FEEDCREATE_HELP: &str =
+    "/feed create - Create the url of your calendar subscription feed.\n\n\
+Usage: /feed create\n\n\
+Example:\n\
+/feed create\n\n\
+Note: If you already have a feed, its old url stops working and calendars subscribed to it \
+have to be subscribed to the new one.";
+
+/// Create the url of your calendar subscription feed, replacing any previous one.
+#[poise::command(prefix_command, slash_command, help_text_fn = "feedcreate_help")]
+pub async fn create(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let Some(config) = feed_config(ctx).await? else {
+        return Ok(());
+    };
+
+    let user = User::new_feed_token(ctx.author().id, &ctx.data().connections.db).await?;
+
+    match user.as_ref().and_then(User::feed_token) {
+        Some(token) => {
+            ctx.say(format!(
+                "Subscribe to this url in your calendar app:\n<{}>\n\nKeep it to yourself, anyone with it can see your timetable.",
+                config.feed_url(token)
+            ))
+            .await?;
+        }
+        None => {
+            ctx.say("Not logged in, login using `/login` to get started.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn feedcreate_help() -> String {
+    FEEDCREATE_HELP.into()
+}
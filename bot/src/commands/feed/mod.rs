@@ -0,0 +1,48 @@
+use crate::server::FeedConfig;
+use crate::{CommandResult, Context};
+use create::create;
+use revoke::revoke;
+use show::show;
+
+mod create;
+mod revoke;
+mod show;
+
+static FEED_HELP: &str = "/feed [subcommands] - Commands to manage your calendar subscription feed.\n\n\
+Example:\n\
+/feed create\n\
+/feed show\n\
+/feed revoke\n\n\
+Note: Subscribing to the feed url in a calendar app (Google Calendar: Other calendars > From URL) keeps \
+your classes and exams up to date on their own. Anyone with the url can see your timetable, \
+revoke it or create a new one if it gets out.";
+
+/// Reply used when the bot isn't running the feed server
+static FEEDS_DISABLED: &str =
+    "Calendar feeds aren't enabled on this bot, use `/calendar` to export a file instead.";
+
+/// The feed config, or replies that feeds are disabled
+async fn feed_config(ctx: Context<'_>) -> crate::Result<Option<&FeedConfig>> {
+    let config = ctx.data().feed.as_ref();
+    if config.is_none() {
+        ctx.say(FEEDS_DISABLED).await?;
+    }
+    Ok(config)
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "feed_help",
+    aliases("subscribe"),
+    subcommands("create", "show", "revoke")
+)]
+/// Commands to manage your calendar subscription feed.
+pub async fn feed(ctx: Context<'_>) -> CommandResult {
+    ctx.say(FEED_HELP).await?;
+    Ok(())
+}
+
+fn feed_help() -> String {
+    FEED_HELP.into()
+}
@@ -0,0 +1,29 @@
+use crate::{CommandResult, Context};
+use amizone::api::user::User;
+
+static FEEDREVOKE_HELP: &str = "/feed revoke - Revoke your calendar subscription feed.\n\n\
+Usage: /feed revoke\n\n\
+Example:\n\
+/feed revoke\n\n\
+Note: The url stops working right away, calendars subscribed to it stop updating.";
+
+/// Revoke your calendar subscription feed.
+#[poise::command(prefix_command, slash_command, help_text_fn = "feedrevoke_help")]
+pub async fn revoke(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let user = User::revoke_feed_token(ctx.author().id, &ctx.data().connections.db).await?;
+
+    if user.as_ref().and_then(User::feed_token).is_some() {
+        ctx.say("Calendar feed revoked, its url doesn't work anymore.")
+            .await?;
+    } else {
+        ctx.say("You don't have a calendar feed.").await?;
+    }
+
+    Ok(())
+}
+
+fn feedrevoke_help() -> String {
+    FEEDREVOKE_HELP.into()
+}
@@ -0,0 +1,37 @@
+use super::feed_config;
+use crate::{CommandResult, Context};
+use amizone::api::user::User;
+
+static FEEDSHOW_HELP: &str = "/feed show - Show the url of your calendar subscription feed.\n\n\
+Usage: /feed show\n\n\
+Example:\n\
+/feed show\n\n\
+Note: The url is only shown to you. Use `/feed create` first if you don't have a feed yet.";
+
+/// Show the url of your calendar subscription feed.
+#[poise::command(prefix_command, slash_command, help_text_fn = "feedshow_help")]
+pub async fn show(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let Some(config) = feed_config(ctx).await? else {
+        return Ok(());
+    };
+
+    let user = User::from_id(ctx.author().id, &ctx.data().connections.db).await?;
+
+    match user.as_ref().and_then(User::feed_token) {
+        Some(token) => {
+            ctx.say(format!("Your calendar feed:\n<{}>", config.feed_url(token)))
+                .await?;
+        }
+        None => {
+            ctx.say("You don't have a calendar feed, create one with `/feed create`.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn feedshow_help() -> String {
+    FEEDSHOW_HELP.into()
+}
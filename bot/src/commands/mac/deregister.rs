@@ -1,6 +1,6 @@
 use super::is_valid_mac;
 use crate::{CommandResult, Context, Result};
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
 
 static WIFIMACDEREGISTER_HELP: &str = "/deregisterwifimac - DeRegister a WiFi MAC address.\n\n\
         Usage: /deregisterwifimac [mac_address]\n\n\
@@ -29,10 +29,13 @@ pub async fn deregister(ctx: Context<'_>, address: String) -> CommandResult {
         return Ok(());
     };
 
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
-    client.deregister_wifi_mac(address).await?;
+    client.deregister_wifi_mac(&address).await?;
     ctx.say("DeRegistered the MAC succesfully.").await?;
 
     let wifimac = client.get_wifi_mac_info().await?;
@@ -1,5 +1,5 @@
 use crate::{CommandResult, Context, Result};
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
 
 static WIFIMACINFO_HELP: &str = "/wifimacinfo - Retrieve information about WiFi MAC addresses registered on amizone.\n\n\
 Usage: /wifimacinfo\n\n\
@@ -18,7 +18,10 @@ the number of free slots, and the total number of slots available.";
 )]
 pub async fn info(ctx: Context<'_>) -> CommandResult {
     ctx.defer().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
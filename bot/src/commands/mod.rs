@@ -1,9 +1,24 @@
 pub mod attendance;
+pub mod attendance_alerts;
+pub mod attendance_history;
+pub mod attendance_watch;
 pub mod authentication;
+pub mod calendar;
 pub mod courses;
+pub mod daily;
+pub mod dev;
 pub mod exam;
+pub mod exam_reminders;
 pub mod faculty_feedback;
+pub mod feed;
 pub mod mac;
 pub mod meta;
+pub mod plan;
 pub mod profile;
+pub mod reminders;
+pub mod result_alerts;
+pub mod results;
 pub mod schedule;
+pub mod timetable_changes;
+pub mod week;
+pub mod whatif;
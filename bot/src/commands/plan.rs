@@ -0,0 +1,130 @@
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::model::AttendanceRecord;
+use amizone::planner::{PlanStatus, SemesterPlan, Target};
+
+use crate::{CommandResult, Context, Result};
+
+static PLAN_HELP: &str =
+    "/plan - Work out how many classes you can miss, or have to attend, in every course.\n\n\
+Usage: /plan [target]\n\n\
+Aliases: bunk, skip\n\n\
+Arguments:\n\
+- [target]: Optional attendance percentage to stay above, defaults to 75.\n\n\
+Example:\n\
+/plan\n\
+/plan 85\n\n\
+Note: The counts assume the classes are missed (or attended) one after another from now on. \
+Courses below the target show how many classes in a row you need to attend to get back above it.";
+
+/// How many classes you can miss, or have to attend, to stay above a target attendance
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "plan_help",
+    aliases("bunk", "skip")
+)]
+pub async fn plan(
+    ctx: Context<'_>,
+    #[description = "Attendance percentage to stay above, defaults to 75"] target: Option<f64>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    let target = match target {
+        Some(percent) => match Target::from_percent(percent) {
+            Some(target) => target,
+            None => {
+                ctx.say("Target should be a percentage above 0 and at most 100.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => Target::default(),
+    };
+
+    let records = client
+        .get_attendance()
+        .await?
+        .into_iter()
+        .map(AttendanceRecord::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    if records.is_empty() {
+        ctx.say("No attendance records found.").await?;
+        return Ok(());
+    }
+
+    let plan = SemesterPlan::new(&records, target);
+    let overall = plan.overall();
+    let at_risk = plan.at_risk().count();
+    let colour = ctx.data().colourscheme.primary;
+
+    let summary = match at_risk {
+        0 => format!("All courses are at or above **{}**.", target),
+        _ => format!(
+            "**{}** course(s) below **{}**, attend **{}** class(es) across them to catch up.",
+            at_risk,
+            target,
+            plan.must_attend()
+        ),
+    };
+
+    let overall = match overall.percentage() {
+        Some(percentage) => format!(
+            "Overall: `{}` / **{}** = *{:.2}%*",
+            overall.attended, overall.held, percentage
+        ),
+        None => String::from("No classes held yet."),
+    };
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.color(colour)
+                .title(format!("Attendance plan for {}", target))
+                .description(format!("{}\n{}", summary, overall));
+
+            for course in plan.courses.iter() {
+                let status = match course.status {
+                    PlanStatus::CanMiss(0) => String::from("🚨 Can't miss the next class"),
+                    PlanStatus::CanMiss(classes) => {
+                        format!("👍 Can miss the next **{}** class(es)", classes)
+                    }
+                    PlanStatus::MustAttend(classes) => {
+                        format!("👎 Attend the next **{}** class(es)", classes)
+                    }
+                    PlanStatus::Unreachable => String::from("❌ Can't reach the target anymore"),
+                };
+
+                let percentage = course
+                    .attendance
+                    .percentage()
+                    .map_or(String::from("N/A"), |percentage| {
+                        format!("{:.2}%", percentage)
+                    });
+
+                e.field(
+                    course.course.to_string(),
+                    format!(
+                        "`{}` / **{}** = *{}*\n{}",
+                        course.attendance.attended, course.attendance.held, percentage, status
+                    ),
+                    false,
+                );
+            }
+
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn plan_help() -> String {
+    PLAN_HELP.into()
+}
@@ -1,5 +1,6 @@
 use crate::{CommandResult, Context, Result};
-use amizone::api::client::UserClient;
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::model::Profile;
 
 static PROFILE_HELP: &str = "/profile - Retrieve and display your user profile information.\n\n\
 Usage: /profile\n\n\
@@ -12,46 +13,36 @@ The information is presented in an embedded format with different fields.";
 #[poise::command(prefix_command, slash_command, help_text_fn = "profile_help")]
 pub async fn profile(ctx: Context<'_>) -> CommandResult {
     ctx.defer_ephemeral().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
-    let profile = client.get_user_profile().await?;
-
-    let batch = profile.batch;
-    let name = profile.name;
-    let blood_group = profile.blood_group;
-    let dob = profile.date_of_birth.unwrap_or_default();
-    let enrollement_number = profile.enrollment_number;
-    let validity = profile.enrollment_validity.unwrap_or_default();
-    let program = profile.program;
-    let id_number = profile.id_card_number;
-    let uuid = profile.uuid;
+    let profile = Profile::try_from(client.get_user_profile().await?)?;
     let colour = ctx.data().colourscheme.tertiary;
 
     ctx.send(|b| {
         b.embed(|e| {
             e.color(colour)
-                .title(name)
-                .image(format!(
-                    "https://img.amizone.net/AWSImageNew.ashx?Type=7&amp;ID={}",
-                    uuid
-                ))
-                .field("Enrollement Number", enrollement_number, true)
-                .field("Program", program, true)
-                .field("Batch", batch, true)
+                .title(&profile.name)
+                .image(profile.photo_url())
+                .field("Enrollement Number", &profile.enrollment_number, true)
+                .field("Program", &profile.program, true)
+                .field("Batch", &profile.batch, true)
                 .field(
                     "Date Of Birth",
-                    dob.to_string().split('T').next().unwrap_or_default(),
+                    profile.date_of_birth.format("%Y-%m-%d"),
                     true,
                 )
-                .field("Blood Group", blood_group, true)
+                .field("Blood Group", &profile.blood_group, true)
                 .field(
                     "Valid Till",
-                    validity.to_string().split('T').next().unwrap_or_default(),
+                    profile.enrollment_validity.format("%Y-%m-%d"),
                     true,
                 )
-                .field("ID number", id_number, true)
+                .field("ID number", &profile.id_card_number, true)
         })
     })
     .await?;
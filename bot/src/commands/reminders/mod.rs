@@ -0,0 +1,32 @@
+use crate::{CommandResult, Context};
+use set::set;
+use stop::stop;
+
+mod set;
+mod stop;
+
+static REMINDERS_HELP: &str =
+    "/reminders [subcommands] - Commands to get a DM before each of your classes.\n\n\
+Example:\n\
+/reminders set\n\
+/reminders set 15\n\
+/reminders stop\n\n\
+Note: The reminder has the course, the faculty and the room of the class, as they are on Amizone \
+right before it starts. Classes without attendance (marked N/A) aren't reminded of.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "reminders_help",
+    aliases("remind"),
+    subcommands("set", "stop")
+)]
+/// Commands to get a DM before each of your classes.
+pub async fn reminders(ctx: Context<'_>) -> CommandResult {
+    ctx.say(REMINDERS_HELP).await?;
+    Ok(())
+}
+
+fn reminders_help() -> String {
+    REMINDERS_HELP.into()
+}
@@ -0,0 +1,65 @@
+use crate::jobs::reminders::{
+    self, ClassReminders, DEFAULT_MINUTES, KIND, MAX_MINUTES, MIN_MINUTES, SCHEDULE,
+};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+
+static REMINDERSSET_HELP: &str =
+    "/reminders set - Get a DM some minutes before each of your classes.\n\n\
+Usage: /reminders set [minutes]\n\n\
+Arguments:\n\
+- [minutes]: Optional number of minutes before a class to remind you, from 5 to 120. \
+If not provided, you are reminded 10 minutes before.\n\n\
+Example:\n\
+/reminders set\n\
+/reminders set 15\n\n\
+Note: Setting it again changes how early you are reminded.";
+
+/// Get a DM some minutes before each of your classes.
+#[poise::command(prefix_command, slash_command, help_text_fn = "remindersset_help")]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Minutes before a class, defaults to 10"]
+    #[min = 5]
+    #[max = 120]
+    minutes: Option<u32>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let minutes = minutes.unwrap_or(DEFAULT_MINUTES);
+    if !(MIN_MINUTES..=MAX_MINUTES).contains(&minutes) {
+        ctx.say(format!(
+            "Invalid number of minutes, pick one from {} to {}.",
+            MIN_MINUTES, MAX_MINUTES
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // Classes already reminded of today aren't reminded of again
+    let settings = reminders::settings(&ctx.data().connections.db);
+    let key = ctx.author().id.to_string();
+    let mut reminders = settings
+        .get(&key)
+        .await?
+        .unwrap_or_else(|| ClassReminders::new(minutes));
+    reminders.minutes = minutes;
+    settings.put(&key, &reminders).await?;
+
+    ctx.data()
+        .scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say(format!(
+        "You'll get a DM {} minutes before each of your classes.",
+        minutes
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn remindersset_help() -> String {
+    REMINDERSSET_HELP.into()
+}
@@ -0,0 +1,30 @@
+use crate::jobs::reminders::{self, KIND};
+use crate::{CommandResult, Context};
+
+static REMINDERSSTOP_HELP: &str = "/reminders stop - Stop getting reminders before classes.\n\n\
+Usage: /reminders stop\n\n\
+Example:\n\
+/reminders stop";
+
+/// Stop getting reminders before classes.
+#[poise::command(prefix_command, slash_command, help_text_fn = "remindersstop_help")]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    reminders::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Class reminders stopped.").await?;
+    } else {
+        ctx.say("You don't get class reminders.").await?;
+    }
+
+    Ok(())
+}
+
+fn remindersstop_help() -> String {
+    REMINDERSSTOP_HELP.into()
+}
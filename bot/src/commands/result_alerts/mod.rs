@@ -0,0 +1,30 @@
+use crate::{CommandResult, Context};
+use start::start;
+use stop::stop;
+
+mod start;
+mod stop;
+
+static RESULTALERTS_HELP: &str = "/resultalerts [subcommands] - Commands to get a DM when your results come out.\n\n\
+Example:\n\
+/resultalerts start\n\
+/resultalerts stop\n\n\
+Note: Your results are checked every half an hour. The DM only has the courses whose grades just came out, \
+along with your new SGPA and CGPA once they are out too.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "resultalerts_help",
+    aliases("resultalert"),
+    subcommands("start", "stop")
+)]
+/// Commands to get a DM when your results come out.
+pub async fn resultalerts(ctx: Context<'_>) -> CommandResult {
+    ctx.say(RESULTALERTS_HELP).await?;
+    Ok(())
+}
+
+fn resultalerts_help() -> String {
+    RESULTALERTS_HELP.into()
+}
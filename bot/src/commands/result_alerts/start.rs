@@ -0,0 +1,38 @@
+use crate::jobs::results::{self, ResultAlerts, KIND, SCHEDULE};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+
+static RESULTALERTSSTART_HELP: &str =
+    "/resultalerts start - Get a DM when your results come out.\n\n\
+Usage: /resultalerts start\n\n\
+Example:\n\
+/resultalerts start\n\n\
+Note: Results already out aren't sent, use `/results` to see them.";
+
+/// Get a DM when your results come out.
+#[poise::command(prefix_command, slash_command, help_text_fn = "resultalertsstart_help")]
+pub async fn start(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let scheduler = &ctx.data().scheduler;
+    if scheduler.get(ctx.author().id, KIND).await?.is_some() {
+        ctx.say("You already get result alerts.").await?;
+        return Ok(());
+    }
+
+    results::settings(&ctx.data().connections.db)
+        .put(&ctx.author().id.to_string(), &ResultAlerts::default())
+        .await?;
+    scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say("You'll get a DM when your results come out.")
+        .await?;
+
+    Ok(())
+}
+
+fn resultalertsstart_help() -> String {
+    RESULTALERTSSTART_HELP.into()
+}
@@ -0,0 +1,30 @@
+use crate::jobs::results::{self, KIND};
+use crate::{CommandResult, Context};
+
+static RESULTALERTSSTOP_HELP: &str = "/resultalerts stop - Stop getting result alerts.\n\n\
+Usage: /resultalerts stop\n\n\
+Example:\n\
+/resultalerts stop";
+
+/// Stop getting result alerts.
+#[poise::command(prefix_command, slash_command, help_text_fn = "resultalertsstop_help")]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    results::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Result alerts stopped.").await?;
+    } else {
+        ctx.say("You don't get result alerts.").await?;
+    }
+
+    Ok(())
+}
+
+fn resultalertsstop_help() -> String {
+    RESULTALERTSSTOP_HELP.into()
+}
@@ -0,0 +1,164 @@
+use crate::util;
+use crate::{CommandResult, Context, Result};
+use amizone::api::backend::AmizoneBackend;
+use amizone::api::types::AmizoneClient;
+use amizone::model::{CourseResult, ExamResults, Semester, SemesterGrades};
+use log::debug;
+use poise::serenity_prelude::CreateEmbed;
+
+static RESULTS_HELP: &str = "/results - View your exam results, semester by semester.\n\n\
+Usage: /results [semester]\n\n\
+Aliases: result, grades\n\n\
+Arguments:\n\
+- [semester]: Optional semester number. If provided, it only shows the results for the specified semester. \
+If not provided, it lets you pick from all your semesters.\n\n\
+Example:\n\
+/results\n\
+/results 2\n\n\
+Note: This command shows the course-wise grades along with the SGPA and CGPA for a semester. \
+Semesters whose results have not been published yet are shown as empty, \
+ones that couldn't be fetched are shown as unavailable.";
+
+/// View your course-wise grades along with your SGPA and CGPA
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "results_help",
+    aliases("result", "grades")
+)]
+pub async fn results(
+    ctx: Context<'_>,
+    #[description = "Semester number, defaults to all semesters"] semester: Option<usize>,
+) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    let semesters = client
+        .get_semesters()
+        .await?
+        .into_iter()
+        .map(Semester::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|Semester { number, .. }| semester.map_or(true, |wanted| wanted == *number))
+        .collect::<Vec<Semester>>();
+
+    if semesters.is_empty() {
+        ctx.say("No semesters found.").await?;
+        return Ok(());
+    }
+
+    let numbers = semesters
+        .iter()
+        .map(|semester| semester.number)
+        .collect::<Vec<_>>();
+    let mut fetched = client.get_exam_results(&numbers).await;
+
+    let mut results = Vec::with_capacity(semesters.len());
+    for Semester { name, number } in semesters {
+        let result = match fetched
+            .remove(&number)
+            .map(|results| results.map(ExamResults::try_from))
+        {
+            Some(Ok(Ok(result))) => Some(result),
+            Some(Ok(Err(err))) => {
+                debug!("Results of semester {} couldn't be read: {}", number, err);
+                None
+            }
+            Some(Err(err)) => {
+                debug!(
+                    "Results of semester {} couldn't be fetched: {}",
+                    number, err
+                );
+                None
+            }
+            None => None,
+        };
+
+        // Semesters that couldn't be fetched are still listed, as unavailable
+        results.push(SemesterResult {
+            name,
+            overall: result
+                .as_ref()
+                .and_then(|result| result.for_semester(number).copied()),
+            records: result.map(|result| result.courses),
+        });
+    }
+
+    let options = results
+        .iter()
+        .map(|result| match result.records {
+            Some(_) => result.name.clone(),
+            None => format!("{} (unavailable)", result.name),
+        })
+        .collect::<Vec<String>>();
+    let options = options.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    util::make_select_menu(
+        ctx,
+        results.as_slice(),
+        options.as_slice(),
+        "Select Semester",
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn results_help() -> String {
+    RESULTS_HELP.into()
+}
+
+struct SemesterResult {
+    name: String,
+    /// `None` if the results couldn't be fetched
+    records: Option<Vec<CourseResult>>,
+    overall: Option<SemesterGrades>,
+}
+
+impl From<&SemesterResult> for CreateEmbed {
+    fn from(value: &SemesterResult) -> Self {
+        let mut embed = CreateEmbed::default();
+
+        embed.title(&value.name);
+
+        let Some(ref records) = value.records else {
+            embed.description("_Results of this semester couldn't be fetched, try again later._");
+            return embed;
+        };
+
+        match value.overall {
+            Some(ref overall) => embed.description(format!(
+                "**SGPA:** `{:.2}` | **CGPA:** `{:.2}`",
+                overall.sgpa, overall.cgpa
+            )),
+            None => embed.description("_SGPA and CGPA not published yet._"),
+        };
+
+        if records.is_empty() {
+            embed.field(
+                "No results",
+                "Results for this semester are not out yet.",
+                false,
+            );
+        }
+
+        for record in records.iter() {
+            embed.field(
+                format!("{} {}", record.course.code, record.course.name),
+                format!(
+                    "Grade: **{}** (`{}`) | Credits: `{}`",
+                    record.grade, record.grade_point, record.credits.acquired
+                ),
+                false,
+            );
+        }
+
+        embed
+    }
+}
@@ -2,10 +2,11 @@ use std::ops::{Deref, DerefMut};
 
 use crate::{CommandResult, Context, Result};
 use amizone::api::{
-    client::UserClient,
-    types::{AttendanceState, Date, ScheduledClass},
+    backend::AmizoneBackend,
+    types::{AmizoneClient, AttendanceState, Date},
 };
-use chrono::{prelude::Utc, Datelike, FixedOffset};
+use amizone::model::{self, ClassSlot};
+use chrono::Datelike;
 use poise::serenity_prelude::CreateEmbed;
 
 static SCHEDULE_HELP: &str = "/schedule - View the schedule of classes.\n\n\
@@ -38,14 +39,15 @@ pub async fn schedule(
     #[description = "Year, defualts to current year"] year: Option<String>,
 ) -> CommandResult {
     ctx.defer().await?;
-    let mut invocation_data = ctx.invocation_data::<Result<UserClient>>().await.unwrap();
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
 
     let client = invocation_data.as_mut()?;
 
     let color = ctx.data().colourscheme.primary;
-    let now = Utc::now()
-        .with_timezone(&FixedOffset::east_opt(5 * 60 * 60 + 30 * 60).unwrap())
-        .date_naive();
+    let now = model::today();
 
     let date = Date {
         day: match day {
@@ -102,7 +104,12 @@ pub async fn schedule(
     };
 
     let schedule = ScheduledClasses {
-        schedule: client.get_class_schedule(date.clone()).await?,
+        schedule: client
+            .get_class_schedule(date.clone())
+            .await?
+            .into_iter()
+            .map(ClassSlot::try_from)
+            .collect::<std::result::Result<_, _>>()?,
         date: date.clone(),
     };
 
@@ -128,9 +135,9 @@ fn schedule_help() -> String {
     SCHEDULE_HELP.into()
 }
 
-struct ScheduledClasses {
-    schedule: Vec<ScheduledClass>,
-    date: Date,
+pub(crate) struct ScheduledClasses {
+    pub schedule: Vec<ClassSlot>,
+    pub date: Date,
 }
 
 impl ScheduledClasses {
@@ -140,7 +147,7 @@ impl ScheduledClasses {
 }
 
 impl Deref for ScheduledClasses {
-    type Target = Vec<ScheduledClass>;
+    type Target = Vec<ClassSlot>;
 
     fn deref(&self) -> &Self::Target {
         &self.schedule
@@ -161,42 +168,22 @@ impl From<&ScheduledClasses> for CreateEmbed {
             "{}-{}-{}",
             value.date.day, value.date.month, value.date.year
         ));
-        embed.description("_ _");
+        if value.is_holiday() {
+            embed.description("No classes scheduled.");
+        } else {
+            embed.description("_ _");
+        }
 
         for class in value.iter() {
-            let course = &class.course.as_ref().unwrap();
-            let name = format!("{} {}", course.code, course.name);
+            let name = format!("{} {}", class.course.code, class.course.name);
 
             let faculty = &class.faculty;
             let room = &class.room;
 
-            let start = match &class.start_time {
-                Some(time) => time
-                    .to_string()
-                    .split('T')
-                    .nth(1)
-                    .unwrap()
-                    .split('Z')
-                    .next()
-                    .unwrap()
-                    .to_string(),
-                None => String::from("?"),
-            };
-
-            let end = match &class.end_time {
-                Some(time) => time
-                    .to_string()
-                    .split('T')
-                    .nth(1)
-                    .unwrap()
-                    .split('Z')
-                    .next()
-                    .unwrap()
-                    .to_string(),
-                None => String::from("?"),
-            };
+            let start = class.start.format("%H:%M");
+            let end = class.end.format("%H:%M");
 
-            let attendance = match &class.attendance.into() {
+            let attendance = match class.attendance {
                 AttendanceState::Pending => "🔵",
                 AttendanceState::Present => "🟢",
                 AttendanceState::Absent => "🔴",
@@ -212,3 +199,180 @@ impl From<&ScheduledClasses> for CreateEmbed {
         embed
     }
 }
+
+/// Classes of a day that were added, removed or changed between two fetches of its schedule.
+/// Attendance isn't compared, it changing is no change to the timetable.
+pub(crate) struct ScheduleDiff {
+    pub date: Date,
+    pub added: Vec<ClassSlot>,
+    pub removed: Vec<ClassSlot>,
+    /// Before and after
+    pub changed: Vec<(ClassSlot, ClassSlot)>,
+}
+
+impl ScheduleDiff {
+    pub fn new(date: Date, before: &[ClassSlot], after: &[ClassSlot]) -> Self {
+        fn same(a: &ClassSlot, b: &ClassSlot) -> bool {
+            a.course.code == b.course.code
+                && a.start == b.start
+                && a.end == b.end
+                && a.room == b.room
+                && a.faculty == b.faculty
+        }
+
+        let mut gone = before
+            .iter()
+            .filter(|class| !after.iter().any(|other| same(class, other)))
+            .collect::<Vec<_>>();
+        gone.sort_by_key(|class| class.start);
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for class in after
+            .iter()
+            .filter(|class| !before.iter().any(|other| same(class, other)))
+        {
+            // A class of a course that's gone was moved or changed, the earliest one is taken
+            match gone
+                .iter()
+                .position(|old| old.course.code == class.course.code)
+            {
+                Some(old) => changed.push((gone.remove(old).clone(), class.clone())),
+                None => added.push(class.clone()),
+            }
+        }
+
+        Self {
+            date,
+            added,
+            removed: gone.into_iter().cloned().collect(),
+            changed,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl From<&ScheduleDiff> for CreateEmbed {
+    fn from(value: &ScheduleDiff) -> Self {
+        let mut embed = CreateEmbed::default();
+        let time = |class: &ClassSlot| {
+            format!(
+                "{} - {}",
+                class.start.format("%H:%M"),
+                class.end.format("%H:%M")
+            )
+        };
+
+        embed.title(format!(
+            "Timetable changed, {}-{}-{}",
+            value.date.day, value.date.month, value.date.year
+        ));
+
+        for class in value.removed.iter() {
+            embed.field(
+                format!("❌ {} {}", class.course.code, class.course.name),
+                format!("Cancelled, was at 🕔 {} in 📍 {}", time(class), class.room),
+                false,
+            );
+        }
+
+        for class in value.added.iter() {
+            embed.field(
+                format!("➕ {} {}", class.course.code, class.course.name),
+                format!(
+                    "🕔 {} \n 📍 {} - {}",
+                    time(class),
+                    class.room,
+                    class.faculty
+                ),
+                false,
+            );
+        }
+
+        for (before, after) in value.changed.iter() {
+            let mut changes = Vec::new();
+            if before.start != after.start || before.end != after.end {
+                changes.push(format!("🕔 {} → **{}**", time(before), time(after)));
+            }
+            if before.room != after.room {
+                changes.push(format!("📍 {} → **{}**", before.room, after.room));
+            }
+            if before.faculty != after.faculty {
+                changes.push(format!("👤 {} → **{}**", before.faculty, after.faculty));
+            }
+
+            embed.field(
+                format!("✏️ {} {}", after.course.code, after.course.name),
+                changes.join("\n"),
+                false,
+            );
+        }
+
+        embed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amizone::model::CourseRef;
+    use chrono::TimeZone;
+
+    fn class(code: &str, hour: u32, room: &str) -> ClassSlot {
+        let start = model::ist()
+            .with_ymd_and_hms(2023, 5, 15, hour, 0, 0)
+            .unwrap();
+        ClassSlot {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            start,
+            end: start + chrono::Duration::hours(1),
+            faculty: String::from("Faculty"),
+            room: room.to_string(),
+            attendance: AttendanceState::Pending,
+        }
+    }
+
+    fn date() -> Date {
+        Date {
+            year: 2023,
+            month: 5,
+            day: 15,
+        }
+    }
+
+    #[test]
+    fn schedule_diff() {
+        let before = [
+            class("A", 9, "101"),
+            class("B", 10, "102"),
+            class("C", 11, "103"),
+        ];
+        let mut marked = before.clone();
+        marked[0].attendance = AttendanceState::Present;
+        assert!(ScheduleDiff::new(date(), &before, &marked).is_empty());
+
+        // B moved an hour later, C cancelled, D added, A in another room
+        let after = [
+            class("A", 9, "201"),
+            class("B", 11, "102"),
+            class("D", 12, "104"),
+        ];
+        let diff = ScheduleDiff::new(date(), &before, &after);
+
+        assert_eq!(diff.added, vec![class("D", 12, "104")]);
+        assert_eq!(diff.removed, vec![class("C", 11, "103")]);
+        assert_eq!(
+            diff.changed,
+            vec![
+                (class("A", 9, "101"), class("A", 9, "201")),
+                (class("B", 10, "102"), class("B", 11, "102"))
+            ]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{CommandResult, Context};
+use start::start;
+use stop::stop;
+
+mod start;
+mod stop;
+
+static TIMETABLECHANGES_HELP: &str = "/timetablechanges [subcommands] - Commands to get a DM when your timetable changes.\n\n\
+Example:\n\
+/timetablechanges start\n\
+/timetablechanges stop\n\n\
+Note: Your classes of today and tomorrow are checked every half an hour. You get a DM when a class is cancelled, \
+added or moved, or its room or faculty changes.";
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "timetablechanges_help",
+    aliases("ttchanges"),
+    subcommands("start", "stop")
+)]
+/// Commands to get a DM when your timetable changes.
+pub async fn timetablechanges(ctx: Context<'_>) -> CommandResult {
+    ctx.say(TIMETABLECHANGES_HELP).await?;
+    Ok(())
+}
+
+fn timetablechanges_help() -> String {
+    TIMETABLECHANGES_HELP.into()
+}
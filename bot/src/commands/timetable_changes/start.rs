@@ -0,0 +1,43 @@
+use crate::jobs::timetable::{self, TimetableSnapshots, KIND, SCHEDULE};
+use crate::scheduler::Job;
+use crate::{CommandResult, Context};
+
+static TIMETABLECHANGESSTART_HELP: &str =
+    "/timetablechanges start - Get a DM when your timetable changes.\n\n\
+Usage: /timetablechanges start\n\n\
+Example:\n\
+/timetablechanges start\n\n\
+Note: Changes are found by comparing your timetable with the one seen at the last check, \
+so you start getting them from the second check on.";
+
+/// Get a DM when your timetable changes.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "timetablechangesstart_help"
+)]
+pub async fn start(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let scheduler = &ctx.data().scheduler;
+    if scheduler.get(ctx.author().id, KIND).await?.is_some() {
+        ctx.say("You already get timetable changes.").await?;
+        return Ok(());
+    }
+
+    timetable::settings(&ctx.data().connections.db)
+        .put(&ctx.author().id.to_string(), &TimetableSnapshots::default())
+        .await?;
+    scheduler
+        .schedule(&Job::new(ctx.author().id, KIND, SCHEDULE))
+        .await?;
+
+    ctx.say("You'll get a DM when a class of today or tomorrow changes.")
+        .await?;
+
+    Ok(())
+}
+
+fn timetablechangesstart_help() -> String {
+    TIMETABLECHANGESSTART_HELP.into()
+}
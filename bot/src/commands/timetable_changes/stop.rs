@@ -0,0 +1,35 @@
+use crate::jobs::timetable::{self, KIND};
+use crate::{CommandResult, Context};
+
+static TIMETABLECHANGESSTOP_HELP: &str =
+    "/timetablechanges stop - Stop getting timetable changes.\n\n\
+Usage: /timetablechanges stop\n\n\
+Example:\n\
+/timetablechanges stop";
+
+/// Stop getting timetable changes.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "timetablechangesstop_help"
+)]
+pub async fn stop(ctx: Context<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+
+    let job = ctx.data().scheduler.remove(ctx.author().id, KIND).await?;
+    timetable::settings(&ctx.data().connections.db)
+        .remove(&ctx.author().id.to_string())
+        .await?;
+
+    if job.is_some() {
+        ctx.say("Timetable changes stopped.").await?;
+    } else {
+        ctx.say("You don't get timetable changes.").await?;
+    }
+
+    Ok(())
+}
+
+fn timetablechangesstop_help() -> String {
+    TIMETABLECHANGESSTOP_HELP.into()
+}
@@ -0,0 +1,74 @@
+use crate::commands::schedule::ScheduledClasses;
+use crate::util;
+use crate::{CommandResult, Context, Result};
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::model::{self, ClassSlot};
+use chrono::{Datelike, Duration};
+
+static WEEK_HELP: &str = "/week - View the schedule of classes for a whole week.\n\n\
+Usage: /week [weeks]\n\n\
+Aliases: timetable\n\n\
+Arguments:\n\
+- [weeks]: Optional number of weeks from this one, 1 for next week, -1 for last week. \
+If not provided, it shows this week.\n\n\
+Example:\n\
+/week\n\
+/week 1\n\n\
+Note: This command shows the schedule from Monday to Sunday, pick a day from the menu to see its classes.";
+
+/// View the schedule of classes for a whole week
+#[poise::command(
+    prefix_command,
+    slash_command,
+    help_text_fn = "week_help",
+    aliases("timetable")
+)]
+pub async fn week(
+    ctx: Context<'_>,
+    #[description = "Weeks from this one, eg. 1 for next week or -1 for last week"]
+    #[min = -52]
+    #[max = 52]
+    weeks: Option<i64>,
+) -> CommandResult {
+    ctx.defer().await?;
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    let today = model::today();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64)
+        + Duration::weeks(weeks.unwrap_or_default());
+    let sunday = monday + Duration::days(6);
+
+    let mut days = Vec::with_capacity(7);
+    let mut options = Vec::with_capacity(7);
+    for (date, classes) in client.get_schedule_range(monday, sunday).await? {
+        let day = ScheduledClasses {
+            schedule: classes
+                .into_iter()
+                .map(ClassSlot::try_from)
+                .collect::<std::result::Result<_, _>>()?,
+            date: date.into(),
+        };
+
+        options.push(if day.is_holiday() {
+            format!("{} (no classes)", date.format("%A %-d %B"))
+        } else {
+            date.format("%A %-d %B").to_string()
+        });
+        days.push(day);
+    }
+
+    let options = options.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    util::make_select_menu(ctx, days.as_slice(), options.as_slice(), "Select Day").await?;
+
+    Ok(())
+}
+
+fn week_help() -> String {
+    WEEK_HELP.into()
+}
@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use crate::{ApplicationContext, CommandResult, Result};
+use amizone::api::{backend::AmizoneBackend, types::AmizoneClient};
+use amizone::gpa::{Grade, ProjectedCourse, Transcript};
+use amizone::model::{Course, ExamResults, Semester};
+use log::debug;
+use poise::serenity_prelude as serenity;
+use poise::Modal;
+
+static WHATIF_HELP: &str = "/whatif - Work out your SGPA and CGPA with the grades you expect this semester.\n\n\
+Usage: /whatif\n\n\
+Example:\n\
+/whatif\n\n\
+Note: This command gives you a button that opens a form to write the grade you expect and the credits of each of \
+your current courses in, eg. `CSE101 A+ 4`. The form has a line for each of your courses already, \
+courses left with just the code are skipped. \
+Grades go from A+ (10) to F (0). Your published results from every semester are used for the rest, \
+so the CGPA is worked out the same way Amizone does it.";
+
+/// Project your SGPA and CGPA with the grades you expect this semester
+#[poise::command(slash_command, help_text_fn = "whatif_help")]
+pub async fn whatif(ctx: ApplicationContext<'_>) -> CommandResult {
+    ctx.defer_ephemeral().await?;
+    let mut invocation_data = ctx
+        .invocation_data::<Result<AmizoneClient>>()
+        .await
+        .unwrap();
+
+    let client = invocation_data.as_mut()?;
+
+    let courses = client
+        .get_current_courses()
+        .await?
+        .into_iter()
+        .map(Course::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut grades = String::from("# course grade credits, eg. CSE101 A+ 4\n");
+    for course in courses {
+        grades.push_str(&course.course.code);
+        grades.push_str(" \n");
+    }
+
+    // A form has to be the first response to an interaction, there's no time to fetch the
+    // courses before it, so it's opened from a button instead
+    let button_id = format!("{}grades", ctx.id());
+    let msg = ctx
+        .send(|b| {
+            b.content("Write in the grades you expect this semester.")
+                .components(|c| {
+                    c.create_action_row(|r| {
+                        r.create_button(|b| b.custom_id(&button_id).label("Enter grades"))
+                    })
+                })
+        })
+        .await?;
+
+    let reply = |content: String| {
+        msg.edit(poise::Context::Application(ctx), move |b| {
+            b.content(content).components(|c| c)
+        })
+    };
+
+    let Some(form) = grades_form(ctx, button_id, GradesForm { grades }).await? else {
+        reply(String::from("No input provided")).await?;
+        return Ok(());
+    };
+
+    let projected = match parse_grades(&form.grades) {
+        Ok(projected) if projected.is_empty() => {
+            reply(String::from(
+                "No grades entered, write them after the course codes, eg. `CSE101 A+ 4`.",
+            ))
+            .await?;
+            return Ok(());
+        }
+        Ok(projected) => projected,
+        Err(err) => {
+            reply(err).await?;
+            return Ok(());
+        }
+    };
+
+    reply(String::from("*Working out your GPA...*")).await?;
+
+    let semesters = client
+        .get_semesters()
+        .await?
+        .into_iter()
+        .map(Semester::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let Some(current) = semesters.iter().map(|semester| semester.number).max() else {
+        msg.edit(poise::Context::Application(ctx), |b| {
+            b.content("No semesters found.")
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let numbers = semesters
+        .iter()
+        .map(|semester| semester.number)
+        .collect::<Vec<_>>();
+
+    // Semesters without published results, like the current one usually, are left out, so
+    // are ones that couldn't be fetched but the user is told about those
+    let mut transcript = Transcript::new();
+    let mut unavailable = Vec::new();
+    for (number, results) in client.get_exam_results(&numbers).await {
+        let results = match results.map(ExamResults::try_from) {
+            Ok(Ok(results)) => results,
+            Ok(Err(err)) => {
+                debug!("Results of semester {} couldn't be read: {}", number, err);
+                unavailable.push(number);
+                continue;
+            }
+            Err(err) => {
+                debug!(
+                    "Results of semester {} couldn't be fetched: {}",
+                    number, err
+                );
+                unavailable.push(number);
+                continue;
+            }
+        };
+
+        if !results.courses.is_empty() {
+            transcript.insert(number, results.courses);
+        }
+    }
+
+    let what_if = transcript.what_if(current, &projected);
+    let gpa = |value: Option<f64>| value.map_or(String::from("N/A"), |gpa| format!("{:.2}", gpa));
+    let colour = ctx.data().colourscheme.primary;
+
+    msg.edit(poise::Context::Application(ctx), |b| {
+        b.content("").embed(|e| {
+            e.color(colour)
+                .title(format!("What if, semester {}", current))
+                .description(format!(
+                    "**SGPA:** `{}` | **CGPA:** `{}`\nCGPA before this semester: `{}`",
+                    gpa(what_if.sgpa),
+                    gpa(what_if.cgpa),
+                    gpa(transcript.cgpa(current.saturating_sub(1))),
+                ));
+
+            if !unavailable.is_empty() {
+                let semesters = unavailable
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                e.footer(|f| {
+                    f.text(format!(
+                        "Results of semester(s) {} couldn't be fetched and are left out.",
+                        semesters.join(", ")
+                    ))
+                });
+            }
+
+            for course in projected.iter() {
+                e.field(
+                    &course.code,
+                    format!(
+                        "Grade: **{}** (`{}`) | Credits: `{}`",
+                        course.grade,
+                        course.grade.grade_point(),
+                        course.credits
+                    ),
+                    true,
+                );
+            }
+
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn whatif_help() -> String {
+    WHATIF_HELP.into()
+}
+
+/// Opens the form once the button is pressed, `None` if it isn't pressed or the form isn't
+/// sent within 10 minutes
+async fn grades_form(
+    ctx: ApplicationContext<'_>,
+    button_id: String,
+    defaults: GradesForm,
+) -> std::result::Result<Option<GradesForm>, serenity::Error> {
+    let timeout = Duration::from_secs(60 * 10);
+    let shard = &ctx.serenity_context.shard;
+
+    let Some(press) = serenity::CollectComponentInteraction::new(shard)
+        .author_id(ctx.author().id)
+        .filter(move |interaction| interaction.data.custom_id == button_id)
+        .timeout(timeout)
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let form_id = press.id.to_string();
+    press
+        .create_interaction_response(ctx.serenity_context, |b| {
+            *b = GradesForm::create(Some(defaults), form_id.clone());
+            b
+        })
+        .await?;
+
+    let Some(response) = serenity::CollectModalInteraction::new(shard)
+        .author_id(ctx.author().id)
+        .filter(move |interaction| interaction.data.custom_id == form_id)
+        .timeout(timeout)
+        .await
+    else {
+        return Ok(None);
+    };
+
+    // Closes the form
+    response
+        .create_interaction_response(ctx.serenity_context, |b| {
+            b.kind(serenity::InteractionResponseType::DeferredUpdateMessage)
+        })
+        .await?;
+
+    Ok(Some(
+        GradesForm::parse(response.data.clone()).map_err(serenity::Error::Other)?,
+    ))
+}
+
+/// Reads `<code> <grade> <credits>` lines, lines with just a code, blank ones and ones
+/// starting with `#` are skipped.
+fn parse_grades(text: &str) -> std::result::Result<Vec<ProjectedCourse>, String> {
+    let mut projected = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (code, grade, credits) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [_] => continue,
+            [code, grade, credits] => (code, grade, credits),
+            _ => {
+                return Err(format!(
+                    "Line {}: expected a course code, grade and credits, eg. `CSE101 A+ 4`.",
+                    number + 1
+                ))
+            }
+        };
+
+        let grade = grade.parse::<Grade>().map_err(|err| {
+            format!(
+                "Line {}: {}, use A+, A, B+, B, C+, C, D or F.",
+                number + 1,
+                err
+            )
+        })?;
+
+        let credits = match credits.parse::<u32>() {
+            Ok(credits) if (1..=30).contains(&credits) => credits,
+            _ => {
+                return Err(format!(
+                    "Line {}: `{}` isn't a number of credits.",
+                    number + 1,
+                    credits
+                ))
+            }
+        };
+
+        projected.push(ProjectedCourse {
+            code: code.to_string(),
+            grade,
+            credits,
+        });
+    }
+
+    Ok(projected)
+}
+
+#[derive(Modal)]
+#[name = "Expected Grades"]
+struct GradesForm {
+    #[name = "Grades"]
+    #[paragraph]
+    #[max_length = 4000]
+    grades: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(code: &str, grade: Grade, credits: u32) -> ProjectedCourse {
+        ProjectedCourse {
+            code: code.to_string(),
+            grade,
+            credits,
+        }
+    }
+
+    #[test]
+    fn grades() {
+        let text = "# course grade credits, eg. CSE101 A+ 4\n\
+            CSE101 A+ 4\n\
+            \n\
+            MAT202\n\
+              PHY103  b 3  \n";
+        assert_eq!(
+            parse_grades(text),
+            Ok(vec![
+                course("CSE101", Grade::APlus, 4),
+                course("PHY103", Grade::B, 3)
+            ])
+        );
+
+        // Just codes, as the form is filled in
+        assert_eq!(parse_grades("CSE101 \nMAT202 \n"), Ok(vec![]));
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert!(parse_grades("CSE101 A+ 4\nMAT202 E 3")
+            .unwrap_err()
+            .starts_with("Line 2:"));
+        assert!(parse_grades("CSE101 A+")
+            .unwrap_err()
+            .starts_with("Line 1:"));
+        assert!(parse_grades("CSE101 A+ 0").is_err());
+        assert!(parse_grades("CSE101 A+ 31").is_err());
+        assert!(parse_grades("CSE101 A+ four").is_err());
+        assert!(parse_grades("CSE101 A+ 30").is_ok());
+    }
+}
@@ -1,7 +1,12 @@
 use std::sync::Arc;
 
-use amizone::api::types::{AmizoneApiError, DbError, StatusCode as ApiStatusCode};
-use log::debug;
+use amizone::api::{
+    store::{CredentialStore, StoreError},
+    types::{AmizoneApiError, DbError, StatusCode as ApiStatusCode},
+    user::credentials::CredentialError,
+};
+use amizone::model::ModelError;
+use log::{debug, error};
 use poise::serenity_prelude::{self as serenity, SerenityError};
 
 use crate::Context;
@@ -12,6 +17,8 @@ pub enum BotError {
     AmizoneError(AmizoneApiError),
     SerenityError(Arc<SerenityError>),
     DbError(DbError),
+    CredentialError(CredentialError),
+    ModelError(ModelError),
     Custom(String),
 }
 
@@ -29,7 +36,22 @@ impl From<AmizoneApiError> for BotError {
 
 impl From<DbError> for BotError {
     fn from(value: DbError) -> Self {
-        BotError::DbError(value)
+        match value {
+            StoreError::Credentials(err) => BotError::CredentialError(err),
+            err => BotError::DbError(err),
+        }
+    }
+}
+
+impl From<CredentialError> for BotError {
+    fn from(value: CredentialError) -> Self {
+        BotError::CredentialError(value)
+    }
+}
+
+impl From<ModelError> for BotError {
+    fn from(value: ModelError) -> Self {
+        BotError::ModelError(value)
     }
 }
 
@@ -56,13 +78,23 @@ impl BotError {
         match self {
             BotError::AmizoneError(err) => {
                 debug!("API Error: {}", err);
-                if let ApiStatusCode::Internal = err.code() {
-                    ctx.say(format!("Operation failed, {}", err.message()))
-                        .await
-                        .ok();
-                    return;
+                match err.code() {
+                    ApiStatusCode::Internal => {
+                        ctx.say(format!("Operation failed, {}", err.message()))
+                            .await
+                            .ok();
+                    }
+                    ApiStatusCode::Unavailable
+                    | ApiStatusCode::DeadlineExceeded
+                    | ApiStatusCode::Cancelled => {
+                        ctx.say("Amizone is not reachable right now, try again in a bit.")
+                            .await
+                            .ok();
+                    }
+                    _ => {
+                        ctx.say("Amizone API returned an error.").await.ok();
+                    }
                 }
-                ctx.say("Amizone API returned an error.").await.ok();
             }
             BotError::SerenityError(err) => {
                 debug!("Discord Error: {}", err);
@@ -72,6 +104,56 @@ impl BotError {
                 debug!("Database Error: {}", err);
                 ctx.say("Error retreving database.").await.ok();
             }
+            BotError::CredentialError(err) => {
+                debug!("Credential Error: {}", err);
+                if err.is_corrupt_record() {
+                    // Nothing can be done with the stored record anymore, throw it away (and
+                    // everything kept about the user, like `/logout` does) so the user can log
+                    // in afresh
+                    let data = ctx.data();
+                    let user = ctx.author().id;
+                    data.connections
+                        .db
+                        .remove_user(&user.to_string())
+                        .await
+                        .ok();
+                    data.scheduler.remove_user(user).await.ok();
+                    crate::jobs::forget_user(&data.connections.db, user)
+                        .await
+                        .ok();
+                    if let Some(ref cache) = data.response_cache {
+                        cache.invalidate_user(&user.to_string());
+                    }
+
+                    ctx.say("Your saved credentials couldn't be read, please log in again using `/login`.")
+                        .await
+                        .ok();
+                } else if *err == CredentialError::Authentication {
+                    // A wrong or missing key fails every record the same way, the records are
+                    // kept so fixing the keys brings them back
+                    error!(
+                        "Credentials of {} failed to decrypt, check the encryption keys: {}",
+                        ctx.author().id,
+                        err
+                    );
+                    ctx.say(
+                        "Your saved credentials couldn't be decrypted, try again in a bit. \
+                    If it keeps happening, contact the developer.",
+                    )
+                    .await
+                    .ok();
+                } else {
+                    ctx.say("Error reading your saved credentials, file an issue.")
+                        .await
+                        .ok();
+                }
+            }
+            BotError::ModelError(err) => {
+                debug!("Model Error: {}", err);
+                ctx.say("Amizone sent something the bot couldn't make sense of, file an issue.")
+                    .await
+                    .ok();
+            }
             BotError::Custom(err) => {
                 debug!("Unhadled Custom Error: {}", err);
                 ctx.say("Error with the command, file an issue.").await.ok();
@@ -86,6 +168,8 @@ impl std::fmt::Display for BotError {
             BotError::AmizoneError(err) => write!(f, "Amizone error: {}", err),
             BotError::SerenityError(err) => write!(f, "Serenity error: {}", err),
             BotError::DbError(err) => write!(f, "Database error: {}", err),
+            BotError::CredentialError(err) => write!(f, "Credential error: {}", err),
+            BotError::ModelError(err) => write!(f, "Model error: {}", err),
             BotError::Custom(err) => {
                 write!(f, "Custom error () really should be handled): {}", err)
             }
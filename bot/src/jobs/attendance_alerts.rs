@@ -0,0 +1,265 @@
+//! A DM when the attendance of a course drops below the user's target, or gets close to it.
+//!
+//! Every course has a level: fine, in the warning band just above the target, or below it.
+//! Only a course getting to a worse level is told about, and a course only gets back to a
+//! better level once it's clear of the line by [`HYSTERESIS`], so attendance going back and
+//! forth around the line doesn't send a DM after every class.
+
+use std::collections::BTreeMap;
+
+use amizone::api::{backend::AmizoneBackend, store::Documents, types::DatabaseConnection};
+use amizone::model::AttendanceRecord;
+use amizone::planner::{PlanStatus, Target};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "attendance_alerts";
+const COLLECTION: &str = "attendance_alerts";
+
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 2 * 60 * 60 };
+/// Percentage points above the target that count as close to it
+pub const DEFAULT_BAND: f64 = 5.0;
+pub const MAX_BAND: f64 = 25.0;
+/// Percentage points a course has to climb past a line before it counts as back above it
+const HYSTERESIS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    #[default]
+    Fine,
+    Warning,
+    Below,
+}
+
+/// Alert settings of a user, and the level every course was at when last checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceAlerts {
+    /// Percentage to stay above
+    pub threshold: f64,
+    /// Percentage points above `threshold` that get a warning
+    pub band: f64,
+    /// Codes of the courses that aren't alerted about
+    #[serde(default)]
+    pub muted: Vec<String>,
+    #[serde(default)]
+    levels: BTreeMap<String, Level>,
+}
+
+impl AttendanceAlerts {
+    pub fn new(threshold: f64, band: f64) -> Self {
+        Self {
+            threshold,
+            band,
+            muted: Vec::new(),
+            levels: BTreeMap::new(),
+        }
+    }
+
+    pub fn target(&self) -> Target {
+        Target::from_percent(self.threshold).unwrap_or_default()
+    }
+
+    pub fn is_muted(&self, code: &str) -> bool {
+        self.muted
+            .iter()
+            .any(|muted| muted.eq_ignore_ascii_case(code))
+    }
+
+    /// Level of a course at `percentage` that was at `previous`
+    fn level(&self, percentage: Option<f64>, previous: Level) -> Level {
+        let Some(percentage) = percentage else {
+            return Level::Fine;
+        };
+
+        let level = |margin: f64| {
+            if percentage < self.threshold + margin {
+                Level::Below
+            } else if percentage < self.threshold + self.band + margin {
+                Level::Warning
+            } else {
+                Level::Fine
+            }
+        };
+
+        match level(0.0) {
+            worse if worse >= previous => worse,
+            _ => level(HYSTERESIS).min(previous),
+        }
+    }
+
+    /// Updates the levels of the courses to the ones of `records`, returns the courses that
+    /// got to a worse level and aren't muted
+    pub fn update(&mut self, records: &[AttendanceRecord]) -> Vec<(AttendanceRecord, Level)> {
+        let mut worse = Vec::new();
+        let mut levels = BTreeMap::new();
+
+        for record in records {
+            let previous = self
+                .levels
+                .get(&record.course.code)
+                .copied()
+                .unwrap_or_default();
+            let level = self.level(record.attendance.percentage(), previous);
+
+            if level > previous && !self.is_muted(&record.course.code) {
+                worse.push((record.clone(), level));
+            }
+            levels.insert(record.course.code.clone(), level);
+        }
+
+        // Courses of past semesters are dropped along the way
+        self.levels = levels;
+        worse
+    }
+}
+
+/// Alert settings of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<AttendanceAlerts> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct AttendanceAlert;
+
+#[async_trait]
+impl JobHandler for AttendanceAlert {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut alerts) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let client = ctx.uncached_client(job.user_id()).await?;
+        let records = client
+            .get_attendance()
+            .await?
+            .into_iter()
+            .map(AttendanceRecord::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let worse = alerts.update(&records);
+        if !worse.is_empty() {
+            let target = alerts.target();
+            let colour = ColourScheme::amity_colours().tertiary;
+
+            ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour)
+                        .title("Attendance alert")
+                        .footer(|f| f.text("Mute a course with /attendancealerts mute"));
+
+                    for (record, _) in worse.iter() {
+                        let attendance = record.attendance;
+                        let status = match attendance.plan(target) {
+                            PlanStatus::MustAttend(classes) => format!(
+                                "Below **{}**, attend **{}** class(es) in a row to get back above it.",
+                                target, classes
+                            ),
+                            PlanStatus::Unreachable => format!("Below **{}**.", target),
+                            PlanStatus::CanMiss(classes) => format!(
+                                "Close to **{}**, you can miss **{}** more class(es).",
+                                target, classes
+                            ),
+                        };
+
+                        e.field(
+                            format!("{} {}", record.course.code, record.course.name),
+                            format!(
+                                "`{}` / **{}** = *{:.2}%*\n{}",
+                                attendance.attended,
+                                attendance.held,
+                                attendance.percentage().unwrap_or(100.0),
+                                status
+                            ),
+                            false,
+                        );
+                    }
+
+                    e
+                })
+            })
+            .await?;
+        }
+
+        settings.put(&key, &alerts).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amizone::model::{Attendance, CourseRef};
+
+    fn record(code: &str, attended: u32, held: u32) -> AttendanceRecord {
+        AttendanceRecord {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            attendance: Attendance { attended, held },
+        }
+    }
+
+    fn levels(worse: Vec<(AttendanceRecord, Level)>) -> Vec<(String, Level)> {
+        worse
+            .into_iter()
+            .map(|(record, level)| (record.course.code, level))
+            .collect()
+    }
+
+    #[test]
+    fn alerts_once_per_level() {
+        let mut alerts = AttendanceAlerts::new(75.0, DEFAULT_BAND);
+
+        // 90%, 78%, 70% and nothing held yet
+        let worse = alerts.update(&[
+            record("A", 9, 10),
+            record("B", 39, 50),
+            record("C", 7, 10),
+            record("D", 0, 0),
+        ]);
+        assert_eq!(
+            levels(worse),
+            vec![("B".into(), Level::Warning), ("C".into(), Level::Below)]
+        );
+
+        // Same levels, nothing new
+        assert!(alerts
+            .update(&[record("B", 40, 51), record("C", 7, 10)])
+            .is_empty());
+
+        // 74%, then 75.5% is too close to the line to count as back above it
+        assert_eq!(
+            levels(alerts.update(&[record("B", 37, 50)])),
+            vec![("B".into(), Level::Below)]
+        );
+        assert!(alerts.update(&[record("B", 151, 200)]).is_empty());
+        assert!(alerts.update(&[record("B", 37, 50)]).is_empty());
+
+        // 78% is clear of it, dropping to 74% again is alerted about
+        assert!(alerts.update(&[record("B", 39, 50)]).is_empty());
+        assert_eq!(
+            levels(alerts.update(&[record("B", 37, 50)])),
+            vec![("B".into(), Level::Below)]
+        );
+    }
+
+    #[test]
+    fn muted_courses() {
+        let mut alerts = AttendanceAlerts::new(75.0, DEFAULT_BAND);
+        alerts.muted.push("a".into());
+
+        assert!(alerts.update(&[record("A", 1, 10)]).is_empty());
+        assert_eq!(alerts.levels.get("A"), Some(&Level::Below));
+    }
+}
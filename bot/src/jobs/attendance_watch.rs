@@ -0,0 +1,355 @@
+//! A DM when the attendance of one of today's classes is marked, or changes after it was, so
+//! a wrong absent can be disputed while the faculty still remembers the class.
+//!
+//! The attendance of every class of the day is kept in the user's document, classes are
+//! told apart by course and start time. Only changes to present or absent are sent, a class
+//! going back to pending (or N/A) is just recorded. Attendance is often marked late, so
+//! yesterday's classes are still checked until midnight if some of them are pending.
+
+use std::collections::BTreeMap;
+
+use amizone::api::{
+    backend::AmizoneBackend,
+    store::Documents,
+    types::{AttendanceState, DatabaseConnection},
+};
+use amizone::model::{self, ClassSlot};
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "attendance_watch";
+const COLLECTION: &str = "attendance_watch";
+
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 20 * 60 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mark {
+    Pending,
+    Present,
+    Absent,
+    /// N/A or invalid
+    Unknown,
+}
+
+impl From<AttendanceState> for Mark {
+    fn from(value: AttendanceState) -> Self {
+        match value {
+            AttendanceState::Pending => Mark::Pending,
+            AttendanceState::Present => Mark::Present,
+            AttendanceState::Absent => Mark::Absent,
+            AttendanceState::Invalid | AttendanceState::Na => Mark::Unknown,
+        }
+    }
+}
+
+impl Mark {
+    fn is_marked(&self) -> bool {
+        matches!(self, Mark::Present | Mark::Absent)
+    }
+}
+
+/// A class whose attendance changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub class: ClassSlot,
+    pub from: Mark,
+    pub to: Mark,
+}
+
+/// Watch settings of a user, and the attendance of the classes being watched at the last check
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceWatch {
+    /// Only absents are sent
+    #[serde(default)]
+    pub absent_only: bool,
+    /// Attendance of every class of today, and of yesterday while some are pending
+    #[serde(default)]
+    days: BTreeMap<NaiveDate, BTreeMap<String, Mark>>,
+}
+
+fn class_key(class: &ClassSlot) -> String {
+    format!("{}@{}", class.course.code, class.start.timestamp())
+}
+
+impl AttendanceWatch {
+    pub fn new(absent_only: bool) -> Self {
+        Self {
+            absent_only,
+            ..Default::default()
+        }
+    }
+
+    /// Days before `today` with classes still pending, their schedule has to be checked again
+    pub fn pending_days(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        self.days
+            .range(..today)
+            .filter(|(_, marks)| marks.values().any(|mark| *mark == Mark::Pending))
+            .map(|(date, _)| *date)
+            .collect()
+    }
+
+    /// Records the attendance of the classes of `date`, returns the ones that got marked or
+    /// changed since the last check. The first check only records it, classes of a new day
+    /// start out pending.
+    pub fn update(&mut self, date: NaiveDate, classes: &[ClassSlot]) -> Vec<Change> {
+        let first = self.days.is_empty();
+        let previous = self.days.remove(&date).unwrap_or_default();
+
+        let mut changes = Vec::new();
+        let mut marks = BTreeMap::new();
+
+        for class in classes {
+            let key = class_key(class);
+            let from = previous.get(&key).copied().unwrap_or(Mark::Pending);
+            let to = Mark::from(class.attendance);
+
+            if !first && from != to && to.is_marked() && (!self.absent_only || to == Mark::Absent) {
+                changes.push(Change {
+                    class: class.clone(),
+                    from,
+                    to,
+                });
+            }
+            marks.insert(key, to);
+        }
+
+        self.days.insert(date, marks);
+        changes
+    }
+
+    /// Forgets the days before yesterday, and yesterday once none of its classes are pending
+    pub fn prune(&mut self, today: NaiveDate) {
+        let yesterday = today - Duration::days(1);
+        self.days.retain(|date, marks| {
+            *date >= today
+                || (*date == yesterday && marks.values().any(|mark| *mark == Mark::Pending))
+        });
+    }
+}
+
+/// Watch settings of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<AttendanceWatch> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct AttendanceWatcher;
+
+#[async_trait]
+impl JobHandler for AttendanceWatcher {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut watch) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let today = model::today();
+        let client = ctx.uncached_client(job.user_id()).await?;
+        let mut changes = Vec::new();
+
+        watch.prune(today);
+        let mut dates = watch.pending_days(today);
+        dates.push(today);
+
+        for date in dates {
+            let classes = client
+                .get_class_schedule(date.into())
+                .await?
+                .into_iter()
+                .map(ClassSlot::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            changes.extend(watch.update(date, &classes));
+        }
+        watch.prune(today);
+
+        if !changes.is_empty() {
+            let colourscheme = ColourScheme::amity_colours();
+            let colour = if changes.iter().any(|change| change.to == Mark::Absent) {
+                colourscheme.tertiary
+            } else {
+                colourscheme.primary
+            };
+
+            ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour).title("Attendance marked");
+
+                    for Change { class, from, to } in changes.iter() {
+                        let marked = match (from, to) {
+                            (Mark::Pending | Mark::Unknown, Mark::Absent) => {
+                                "🔴 Marked **absent**, if you were there get it corrected soon."
+                            }
+                            (_, Mark::Absent) => "🔴 Changed to **absent**.",
+                            (Mark::Absent, _) => "🟢 Changed to **present**.",
+                            _ => "🟢 Marked **present**.",
+                        };
+
+                        // Classes of yesterday come with their date
+                        let style = if class.start.date_naive() == today {
+                            "t"
+                        } else {
+                            "f"
+                        };
+
+                        e.field(
+                            format!("{} {}", class.course.code, class.course.name),
+                            format!(
+                                "{}\n🕔 <t:{}:{}> - <t:{}:t> | 👤 {} | 📍 {}",
+                                marked,
+                                class.start.timestamp(),
+                                style,
+                                class.end.timestamp(),
+                                class.faculty,
+                                class.room
+                            ),
+                            false,
+                        );
+                    }
+
+                    e
+                })
+            })
+            .await?;
+        }
+
+        settings.put(&key, &watch).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amizone::model::CourseRef;
+    use chrono::TimeZone;
+
+    fn class(code: &str, hour: u32, attendance: AttendanceState) -> ClassSlot {
+        let start = model::ist()
+            .with_ymd_and_hms(2023, 5, 15, hour, 0, 0)
+            .unwrap();
+        ClassSlot {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            start,
+            end: start + chrono::Duration::hours(1),
+            faculty: String::new(),
+            room: String::new(),
+            attendance,
+        }
+    }
+
+    fn changes(changes: Vec<Change>) -> Vec<(String, Mark, Mark)> {
+        changes
+            .into_iter()
+            .map(|Change { class, from, to }| (class.course.code, from, to))
+            .collect()
+    }
+
+    #[test]
+    fn transitions() {
+        let day = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        let mut watch = AttendanceWatch::new(false);
+
+        // The first check only records
+        assert!(watch
+            .update(
+                day,
+                &[
+                    class("A", 9, AttendanceState::Present),
+                    class("B", 10, AttendanceState::Pending)
+                ]
+            )
+            .is_empty());
+
+        let classes = [
+            class("A", 9, AttendanceState::Present),
+            class("B", 10, AttendanceState::Absent),
+        ];
+        assert_eq!(
+            changes(watch.update(day, &classes)),
+            vec![("B".into(), Mark::Pending, Mark::Absent)]
+        );
+        assert!(watch.update(day, &classes).is_empty());
+
+        let classes = [
+            class("A", 9, AttendanceState::Present),
+            class("B", 10, AttendanceState::Present),
+        ];
+        assert_eq!(
+            changes(watch.update(day, &classes)),
+            vec![("B".into(), Mark::Absent, Mark::Present)]
+        );
+
+        // Classes of a new day start out pending
+        let next = day.succ_opt().unwrap();
+        assert_eq!(
+            changes(watch.update(next, &[class("C", 9, AttendanceState::Present)])),
+            vec![("C".into(), Mark::Pending, Mark::Present)]
+        );
+    }
+
+    #[test]
+    fn late_marks() {
+        let day = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        let next = day.succ_opt().unwrap();
+        let mut watch = AttendanceWatch::new(false);
+
+        watch.update(
+            day,
+            &[
+                class("A", 9, AttendanceState::Present),
+                class("B", 10, AttendanceState::Pending),
+            ],
+        );
+        watch.prune(next);
+        assert_eq!(watch.pending_days(next), vec![day]);
+
+        // B is marked after midnight
+        let classes = [
+            class("A", 9, AttendanceState::Present),
+            class("B", 10, AttendanceState::Absent),
+        ];
+        assert_eq!(
+            changes(watch.update(day, &classes)),
+            vec![("B".into(), Mark::Pending, Mark::Absent)]
+        );
+        watch.prune(next);
+        assert!(watch.pending_days(next).is_empty());
+        assert!(!watch.days.contains_key(&day));
+
+        // Pending classes are given up on the day after
+        watch.update(next, &[class("C", 9, AttendanceState::Pending)]);
+        watch.prune(next + Duration::days(2));
+        assert!(watch.days.is_empty());
+    }
+
+    #[test]
+    fn absent_only() {
+        let day = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        let mut watch = AttendanceWatch::new(true);
+        watch.update(day, &[]);
+
+        let classes = [
+            class("A", 9, AttendanceState::Present),
+            class("B", 10, AttendanceState::Absent),
+            class("C", 11, AttendanceState::Na),
+        ];
+        assert_eq!(
+            changes(watch.update(day, &classes)),
+            vec![("B".into(), Mark::Pending, Mark::Absent)]
+        );
+    }
+}
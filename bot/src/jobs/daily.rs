@@ -0,0 +1,58 @@
+//! The day's classes, sent every morning (or whenever the user asked for them).
+
+use amizone::api::backend::AmizoneBackend;
+use amizone::model::ClassSlot;
+use async_trait::async_trait;
+use chrono::{FixedOffset, Utc};
+use log::trace;
+use poise::serenity_prelude::CreateEmbed;
+
+use crate::commands::schedule::ScheduledClasses;
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "daily_timetable";
+
+pub struct DailyTimetable;
+
+#[async_trait]
+impl JobHandler for DailyTimetable {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        // The day is the user's day, a DM at 23:00 UTC is about tomorrow in IST
+        let offset = match job.schedule {
+            Schedule::Daily { offset_minutes, .. } => FixedOffset::east_opt(offset_minutes * 60),
+            Schedule::Every { .. } => None,
+        }
+        .unwrap_or_else(amizone::model::ist);
+        let date = Utc::now().with_timezone(&offset).date_naive();
+
+        let client = ctx.client(job.user_id()).await?;
+        let schedule = ScheduledClasses {
+            schedule: client
+                .get_class_schedule(date.into())
+                .await?
+                .into_iter()
+                .map(ClassSlot::try_from)
+                .collect::<std::result::Result<_, _>>()?,
+            date: date.into(),
+        };
+
+        if schedule.is_holiday() {
+            trace!("No classes for {} on {}, skipping", job.user, date);
+            return Ok(());
+        }
+
+        let mut embed = CreateEmbed::from(&schedule);
+        embed
+            .color(ColourScheme::amity_colours().primary)
+            .footer(|f| f.text("Daily timetable, /daily pause or /daily stop to turn it off"));
+
+        ctx.dm(job.user_id(), |m| m.set_embed(embed)).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+//! DMs about exams on the datesheet: a summary when a new datesheet comes out, and a
+//! reminder of every exam the evening before and some hours before it starts.
+//!
+//! The datesheet is kept in the user's document and fetched again every hour, and right
+//! before a reminder goes out. A reminder that's late (the bot was down, or the user opted in
+//! late) is still sent as long as it makes sense, the evening reminder only until the day of
+//! the exam starts.
+
+use amizone::api::{backend::AmizoneBackend, store::Documents, types::DatabaseConnection};
+use amizone::model::{self, Exam};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::CreateEmbed;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "exam_reminders";
+const COLLECTION: &str = "exam_reminders";
+
+/// How often due reminders are looked for
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 10 * 60 };
+pub const DEFAULT_HOURS: u32 = 2;
+pub const MAX_HOURS: u32 = 12;
+/// Hour (IST) of the day before an exam its evening reminder is sent at
+const EVENING_HOUR: u32 = 18;
+/// The datesheet is fetched again after this many minutes
+const REFRESH_MINUTES: i64 = 60;
+/// Most fields Discord allows in an embed
+const MAX_FIELDS: usize = 25;
+
+/// Exam reminder settings of a user, and the datesheet they were last sent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExamReminders {
+    /// Hours before an exam its last reminder is sent
+    pub hours: u32,
+    /// Title of the last datesheet a summary was sent of
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    fetched: Option<DateTime<Utc>>,
+    #[serde(default)]
+    exams: Vec<UpcomingExam>,
+    /// Keys of the reminders already sent
+    #[serde(default)]
+    reminded: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UpcomingExam {
+    code: String,
+    name: String,
+    time: DateTime<Utc>,
+    mode: String,
+    #[serde(default)]
+    location: Option<String>,
+}
+
+impl UpcomingExam {
+    fn key(&self) -> String {
+        format!("{}@{}", self.code, self.time.timestamp())
+    }
+
+    /// When the evening reminder is sent, and until when
+    fn evening(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let day = self.time.with_timezone(&model::ist()).date_naive();
+        let at = |date: chrono::NaiveDate, hour| {
+            date.and_hms_opt(hour, 0, 0)
+                .and_then(|time| time.and_local_timezone(model::ist()).single())
+                .map_or(self.time, |time| time.with_timezone(&Utc))
+        };
+
+        (at(day - Duration::days(1), EVENING_HOUR), at(day, 0))
+    }
+
+    fn add_fields(&self, embed: &mut CreateEmbed) {
+        embed.field("✍🏼 Mode", &self.mode, true).field(
+            "📍 Location",
+            self.location.as_deref().unwrap_or("Not out yet"),
+            true,
+        );
+    }
+}
+
+impl From<Exam> for UpcomingExam {
+    fn from(value: Exam) -> Self {
+        Self {
+            code: value.course.code,
+            name: value.course.name,
+            time: value.time.with_timezone(&Utc),
+            mode: value.mode,
+            location: value.location,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reminder {
+    /// The evening before
+    Evening,
+    /// Some hours before
+    Soon,
+}
+
+impl Reminder {
+    fn key(&self, exam: &UpcomingExam) -> String {
+        match self {
+            Reminder::Evening => format!("evening:{}", exam.key()),
+            Reminder::Soon => format!("soon:{}", exam.key()),
+        }
+    }
+}
+
+impl ExamReminders {
+    pub fn new(hours: u32) -> Self {
+        Self {
+            hours,
+            ..Default::default()
+        }
+    }
+
+    /// Reminders due at `now` that haven't been sent
+    fn due(&self, now: DateTime<Utc>) -> Vec<(Reminder, UpcomingExam)> {
+        let lead = Duration::hours(self.hours as i64);
+        let mut due = Vec::new();
+
+        for exam in self.exams.iter().filter(|exam| exam.time > now) {
+            let (evening, evening_until) = exam.evening();
+            if exam.time - lead <= now {
+                due.push((Reminder::Soon, exam.clone()));
+            } else if evening <= now && now < evening_until {
+                due.push((Reminder::Evening, exam.clone()));
+            }
+        }
+
+        due.retain(|(reminder, exam)| !self.reminded.contains(&reminder.key(exam)));
+        due
+    }
+
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.fetched.map_or(true, |fetched| {
+            now - fetched >= Duration::minutes(REFRESH_MINUTES)
+        })
+    }
+
+    /// Replaces the datesheet, forgetting reminders of exams that aren't on it anymore
+    fn refresh(&mut self, exams: Vec<UpcomingExam>, now: DateTime<Utc>) {
+        self.reminded.retain(|key| {
+            exams
+                .iter()
+                .any(|exam| key.ends_with(&format!(":{}", exam.key())))
+        });
+        self.exams = exams;
+        self.fetched = Some(now);
+    }
+}
+
+/// Exam reminder settings of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<ExamReminders> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct ExamReminder;
+
+#[async_trait]
+impl JobHandler for ExamReminder {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut reminders) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let colour = ColourScheme::amity_colours().primary;
+        let mut new_title = None;
+
+        if reminders.is_stale(now) || !reminders.due(now).is_empty() {
+            let client = ctx.uncached_client(job.user_id()).await?;
+            let (datesheet_title, datesheet) = client.get_exam_schedule().await?;
+            let exams = datesheet
+                .into_iter()
+                .map(|exam| Exam::try_from(exam).map(UpcomingExam::from))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            if !exams.is_empty() && reminders.title.as_ref() != Some(&datesheet_title) {
+                new_title = Some(datesheet_title);
+            }
+            reminders.refresh(exams, now);
+        }
+
+        if let Some(title) = new_title {
+            ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour)
+                        .title(&title)
+                        .description("A new datesheet is out, you'll be reminded of every exam.");
+
+                    for exam in reminders.exams.iter().take(MAX_FIELDS) {
+                        e.field(
+                            format!("{} {}", exam.code, exam.name),
+                            format!(
+                                "📅 <t:{}:F>\n✍🏼 {}\n📍 {}",
+                                exam.time.timestamp(),
+                                exam.mode,
+                                exam.location.as_deref().unwrap_or("Not out yet")
+                            ),
+                            false,
+                        );
+                    }
+
+                    e
+                })
+            })
+            .await?;
+
+            // Saved right away, so a failed reminder below doesn't send the summary again
+            reminders.title = Some(title);
+            settings.put(&key, &reminders).await?;
+        }
+
+        let mut sent = Ok(());
+        for (reminder, exam) in reminders.due(now) {
+            let description = match reminder {
+                Reminder::Evening => format!("Tomorrow at <t:{}:t>", exam.time.timestamp()),
+                Reminder::Soon => format!("Starts <t:{}:R>", exam.time.timestamp()),
+            };
+
+            let dm = ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour)
+                        .title(format!("{} {}", exam.code, exam.name))
+                        .description(description);
+                    exam.add_fields(e);
+                    e
+                })
+            });
+
+            // Reminders sent before a failed one are still saved, so they aren't sent twice
+            if let Err(err) = dm.await {
+                sent = Err(err);
+                break;
+            }
+            reminders.reminded.push(reminder.key(&exam));
+        }
+
+        settings.put(&key, &reminders).await?;
+
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        model::ist()
+            .with_ymd_and_hms(2023, 5, day, hour, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn exam(code: &str, time: DateTime<Utc>) -> UpcomingExam {
+        UpcomingExam {
+            code: code.to_string(),
+            name: code.to_string(),
+            time,
+            mode: String::from("Offline"),
+            location: None,
+        }
+    }
+
+    fn due(reminders: &ExamReminders, now: DateTime<Utc>) -> Vec<(Reminder, String)> {
+        reminders
+            .due(now)
+            .into_iter()
+            .map(|(reminder, exam)| (reminder, exam.code))
+            .collect()
+    }
+
+    #[test]
+    fn due_reminders() {
+        let mut reminders = ExamReminders::new(2);
+        reminders.refresh(
+            vec![exam("A", at(16, 10)), exam("B", at(17, 14))],
+            at(15, 0),
+        );
+
+        assert!(due(&reminders, at(15, 17)).is_empty());
+        assert_eq!(
+            due(&reminders, at(15, 18)),
+            vec![(Reminder::Evening, "A".into())]
+        );
+
+        reminders
+            .reminded
+            .push(Reminder::Evening.key(&reminders.exams[0]));
+        assert!(due(&reminders, at(15, 23)).is_empty());
+        assert_eq!(
+            due(&reminders, at(16, 8)),
+            vec![(Reminder::Soon, "A".into())]
+        );
+
+        // Too late for the evening reminder of B, only the one right before it is sent
+        assert!(due(&reminders, at(17, 11)).is_empty());
+        assert_eq!(
+            due(&reminders, at(17, 12)),
+            vec![(Reminder::Soon, "B".into())]
+        );
+        assert!(due(&reminders, at(17, 14)).is_empty());
+    }
+
+    #[test]
+    fn forgets_removed_exams() {
+        let mut reminders = ExamReminders::new(2);
+        let exams = vec![exam("A", at(16, 10)), exam("B", at(17, 14))];
+        reminders.refresh(exams.clone(), at(15, 0));
+        reminders.reminded = vec![
+            Reminder::Evening.key(&exams[0]),
+            Reminder::Evening.key(&exams[1]),
+        ];
+
+        // B moved to another day
+        reminders.refresh(
+            vec![exam("A", at(16, 10)), exam("B", at(18, 14))],
+            at(15, 1),
+        );
+        assert_eq!(reminders.reminded, vec![Reminder::Evening.key(&exams[0])]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Snapshots of a user's attendance over the semester, to chart how it moved.
+//!
+//! Amizone only has the current totals, so they are recorded a few times a day and the last
+//! one of every day is kept. Courses that aren't in the attendance anymore (a new semester
+//! started) are forgotten along with their history.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use amizone::api::{
+    backend::AmizoneBackend,
+    store::Documents,
+    types::{AmizoneClient, DatabaseConnection},
+};
+use amizone::model::{self, Attendance, AttendanceRecord};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::Result;
+
+pub const KIND: &str = "attendance_history";
+const COLLECTION: &str = "attendance_history";
+
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 6 * 60 * 60 };
+
+/// Attendance of every course of a user by day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceHistory {
+    #[serde(default)]
+    courses: BTreeMap<String, CourseHistory>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CourseHistory {
+    pub name: String,
+    /// Classes attended and held by day
+    pub days: BTreeMap<NaiveDate, (u32, u32)>,
+}
+
+impl CourseHistory {
+    /// Attendance percentage by day
+    pub fn percentages(&self) -> Vec<(NaiveDate, f64)> {
+        self.days
+            .iter()
+            .filter_map(|(date, &(attended, held))| {
+                Attendance { attended, held }
+                    .percentage()
+                    .map(|percentage| (*date, percentage))
+            })
+            .collect()
+    }
+}
+
+impl AttendanceHistory {
+    /// Records `records` as the attendance of `date`, replacing what was recorded that day.
+    /// An empty attendance isn't recorded, it's more likely Amizone acting up than a new
+    /// semester without courses.
+    pub fn record(&mut self, date: NaiveDate, records: &[AttendanceRecord]) {
+        if records.is_empty() {
+            return;
+        }
+
+        let mut courses = BTreeMap::new();
+        for AttendanceRecord { course, attendance } in records {
+            let mut history = self.courses.remove(&course.code).unwrap_or_default();
+            history.name = course.name.clone();
+            if attendance.held > 0 {
+                history
+                    .days
+                    .insert(date, (attendance.attended, attendance.held));
+            }
+            courses.insert(course.code.clone(), history);
+        }
+
+        self.courses = courses;
+    }
+
+    /// History of every course by code
+    pub fn courses(&self) -> &BTreeMap<String, CourseHistory> {
+        &self.courses
+    }
+
+    /// Days anything was recorded on
+    pub fn days(&self) -> usize {
+        self.courses
+            .values()
+            .flat_map(|history| history.days.keys())
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+}
+
+/// Attendance history of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<AttendanceHistory> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+/// Fetches the attendance of a user and records it as today's
+pub async fn snapshot(client: &AmizoneClient, history: &mut AttendanceHistory) -> Result<()> {
+    let records = client
+        .get_attendance()
+        .await?
+        .into_iter()
+        .map(AttendanceRecord::try_from)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    history.record(model::today(), &records);
+
+    Ok(())
+}
+
+pub struct AttendanceSnapshot;
+
+#[async_trait]
+impl JobHandler for AttendanceSnapshot {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut history) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let client = ctx.client(job.user_id()).await?;
+        snapshot(&client, &mut history).await?;
+        settings.put(&key, &history).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amizone::model::CourseRef;
+
+    fn record(code: &str, attended: u32, held: u32) -> AttendanceRecord {
+        AttendanceRecord {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            attendance: Attendance { attended, held },
+        }
+    }
+
+    #[test]
+    fn one_snapshot_a_day() {
+        let day = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        let next = day.succ_opt().unwrap();
+        let mut history = AttendanceHistory::default();
+
+        // Courses without classes held yet have no percentage to chart
+        history.record(day, &[record("A", 3, 4), record("B", 0, 0)]);
+        history.record(day, &[record("A", 4, 5), record("B", 0, 0)]);
+        history.record(next, &[record("A", 4, 6), record("B", 1, 1)]);
+        history.record(next, &[]);
+
+        assert_eq!(history.days(), 2);
+        assert_eq!(
+            history.courses()["A"].percentages(),
+            vec![(day, 80.0), (next, 4.0 / 6.0 * 100.0)]
+        );
+        assert_eq!(history.courses()["B"].percentages(), vec![(next, 100.0)]);
+
+        // A new semester
+        history.record(next, &[record("C", 1, 1)]);
+        assert_eq!(history.courses().keys().collect::<Vec<_>>(), vec!["C"]);
+    }
+}
@@ -0,0 +1,47 @@
+//! What the background jobs users opt in to actually do, one module per [`JobHandler`].
+//!
+//! Settings and state of a user's job are kept in a collection of the job's own, keyed by
+//! user id, and are forgotten with [`forget_user`] when they log out.
+//!
+//! [`JobHandler`]: crate::scheduler::JobHandler
+
+use amizone::api::types::{DatabaseConnection, DbOperationResult};
+use poise::serenity_prelude::UserId;
+
+use crate::scheduler::Scheduler;
+
+pub mod attendance_alerts;
+pub mod attendance_watch;
+pub mod daily;
+pub mod exams;
+pub mod history;
+pub mod reminders;
+pub mod results;
+pub mod timetable;
+
+/// Registers the handler of every kind of job
+pub fn register(scheduler: &mut Scheduler) {
+    scheduler
+        .register(daily::DailyTimetable)
+        .register(reminders::ClassReminder)
+        .register(attendance_alerts::AttendanceAlert)
+        .register(results::ResultAlert)
+        .register(exams::ExamReminder)
+        .register(attendance_watch::AttendanceWatcher)
+        .register(timetable::TimetableChanges)
+        .register(history::AttendanceSnapshot);
+}
+
+/// Removes what every job kept about `user`
+pub async fn forget_user(db: &DatabaseConnection, user: UserId) -> DbOperationResult<()> {
+    let key = user.to_string();
+    reminders::settings(db).remove(&key).await?;
+    attendance_alerts::settings(db).remove(&key).await?;
+    results::settings(db).remove(&key).await?;
+    exams::settings(db).remove(&key).await?;
+    attendance_watch::settings(db).remove(&key).await?;
+    timetable::settings(db).remove(&key).await?;
+    history::settings(db).remove(&key).await?;
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+//! A DM some minutes before each class starts, with where it is and who takes it.
+//!
+//! The day's schedule is kept in the user's document and fetched again every so often, and
+//! right before a reminder goes out, so a class that moved or changed rooms is reminded of
+//! as it is now. Classes are told apart by course and start time, a class moved to a later
+//! time is reminded of again.
+
+use amizone::api::{
+    backend::AmizoneBackend,
+    store::Documents,
+    types::{AttendanceState, DatabaseConnection},
+};
+use amizone::model::{self, ClassSlot};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use log::trace;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "class_reminders";
+const COLLECTION: &str = "class_reminders";
+
+/// How often due reminders are looked for
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 60 };
+pub const DEFAULT_MINUTES: u32 = 10;
+/// Checks can be more than a minute apart (the schedule, its jitter and the scheduler's tick
+/// add up), a shorter lead could fall between two of them and never be sent
+pub const MIN_MINUTES: u32 = 5;
+pub const MAX_MINUTES: u32 = 120;
+/// The day's schedule is fetched again after this many minutes
+const REFRESH_MINUTES: i64 = 15;
+
+/// Reminder settings of a user, and what they have been reminded of today
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassReminders {
+    /// Minutes before a class its reminder is sent
+    pub minutes: u32,
+    /// Day `classes` and `reminded` are about
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    #[serde(default)]
+    fetched: Option<DateTime<Utc>>,
+    #[serde(default)]
+    classes: Vec<UpcomingClass>,
+    /// Keys of the classes already reminded of
+    #[serde(default)]
+    reminded: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UpcomingClass {
+    code: String,
+    name: String,
+    faculty: String,
+    room: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl UpcomingClass {
+    fn key(&self) -> String {
+        format!("{}@{}", self.code, self.start.timestamp())
+    }
+}
+
+impl From<ClassSlot> for UpcomingClass {
+    fn from(value: ClassSlot) -> Self {
+        Self {
+            code: value.course.code,
+            name: value.course.name,
+            faculty: value.faculty,
+            room: value.room,
+            start: value.start.with_timezone(&Utc),
+            end: value.end.with_timezone(&Utc),
+        }
+    }
+}
+
+impl ClassReminders {
+    pub fn new(minutes: u32) -> Self {
+        Self {
+            minutes,
+            ..Default::default()
+        }
+    }
+
+    /// Classes whose reminder is due at `now` and hasn't been sent
+    fn due(&self, now: DateTime<Utc>) -> Vec<UpcomingClass> {
+        // Settings from before the minimum was raised may be under it
+        let lead = Duration::minutes(self.minutes.max(MIN_MINUTES) as i64);
+        self.classes
+            .iter()
+            .filter(|class| class.start > now && class.start - lead <= now)
+            .filter(|class| !self.reminded.contains(&class.key()))
+            .cloned()
+            .collect()
+    }
+
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.fetched.map_or(true, |fetched| {
+            now - fetched >= Duration::minutes(REFRESH_MINUTES)
+        })
+    }
+}
+
+/// Reminder settings of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<ClassReminders> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct ClassReminder;
+
+#[async_trait]
+impl JobHandler for ClassReminder {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut reminders) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let today = model::today();
+        if reminders.date != Some(today) {
+            reminders.date = Some(today);
+            reminders.fetched = None;
+            reminders.classes.clear();
+            reminders.reminded.clear();
+        }
+
+        if reminders.is_stale(now) || !reminders.due(now).is_empty() {
+            trace!("Fetching the classes of {} for reminders", job.user);
+            let client = ctx.uncached_client(job.user_id()).await?;
+            reminders.classes = client
+                .get_class_schedule(today.into())
+                .await?
+                .into_iter()
+                .map(ClassSlot::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|class| class.attendance != AttendanceState::Na)
+                .map(UpcomingClass::from)
+                .collect();
+            reminders.fetched = Some(now);
+        }
+
+        let colour = ColourScheme::amity_colours().primary;
+        let mut sent = Ok(());
+        for class in reminders.due(now) {
+            let minutes = (class.start - now).num_minutes() + 1;
+            let dm = ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour)
+                        .title(format!("{} {}", class.code, class.name))
+                        .description(format!("Starts in about {} minutes", minutes))
+                        .field(
+                            "🕔 Time",
+                            format!(
+                                "<t:{}:t> - <t:{}:t>",
+                                class.start.timestamp(),
+                                class.end.timestamp()
+                            ),
+                            false,
+                        )
+                        .field("👤 Faculty", &class.faculty, true)
+                        .field("📍 Room", &class.room, true)
+                })
+            });
+
+            // Reminders sent before a failed one are still saved, so they aren't sent twice
+            if let Err(err) = dm.await {
+                sent = Err(err);
+                break;
+            }
+            reminders.reminded.push(class.key());
+        }
+
+        settings.put(&key, &reminders).await?;
+
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn class(code: &str, hour: u32) -> UpcomingClass {
+        let start = model::ist()
+            .with_ymd_and_hms(2023, 5, 15, hour, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        UpcomingClass {
+            code: code.to_string(),
+            name: code.to_string(),
+            faculty: String::new(),
+            room: String::new(),
+            start,
+            end: start + Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn due_reminders() {
+        let mut reminders = ClassReminders::new(10);
+        reminders.classes = vec![class("A", 9), class("B", 10)];
+
+        let at = |hour, minute| {
+            model::ist()
+                .with_ymd_and_hms(2023, 5, 15, hour, minute, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        assert!(reminders.due(at(8, 49)).is_empty());
+        assert_eq!(reminders.due(at(8, 50)), vec![class("A", 9)]);
+        // Not after the class started
+        assert!(reminders.due(at(9, 0)).is_empty());
+
+        reminders.reminded.push(class("B", 10).key());
+        assert!(reminders.due(at(9, 55)).is_empty());
+
+        // Too short a lead is raised to the minimum
+        let mut reminders = ClassReminders::new(1);
+        reminders.classes = vec![class("A", 9)];
+        assert_eq!(reminders.due(at(8, 55)), vec![class("A", 9)]);
+    }
+}
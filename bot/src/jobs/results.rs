@@ -0,0 +1,228 @@
+//! A DM when results of the current semester come out.
+//!
+//! The current results are compared with a snapshot of the ones seen last time, only courses
+//! with a new (or changed) grade and semesters with new SGPA/CGPA are sent. The first check
+//! only takes the snapshot, so opting in doesn't send results that were already out.
+
+use std::collections::BTreeMap;
+
+use amizone::api::{backend::AmizoneBackend, store::Documents, types::DatabaseConnection};
+use amizone::model::{CourseResult, ExamResults, SemesterGrades};
+use async_trait::async_trait;
+use log::trace;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "result_alerts";
+const COLLECTION: &str = "result_alerts";
+
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 30 * 60 };
+
+/// A user's last seen results, `None` until the first check
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResultAlerts {
+    #[serde(default)]
+    snapshot: Option<Snapshot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    /// Grade of every course by code
+    courses: BTreeMap<String, String>,
+    /// SGPA and CGPA of every semester by number
+    overall: BTreeMap<usize, (f32, f32)>,
+}
+
+impl From<&ExamResults> for Snapshot {
+    fn from(value: &ExamResults) -> Self {
+        Self {
+            courses: value
+                .courses
+                .iter()
+                .map(|result| (result.course.code.clone(), result.grade.clone()))
+                .collect(),
+            overall: value
+                .overall
+                .iter()
+                .map(|grades| (grades.semester, (grades.sgpa, grades.cgpa)))
+                .collect(),
+        }
+    }
+}
+
+/// Results that weren't out at the last check
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Published {
+    courses: Vec<CourseResult>,
+    overall: Vec<SemesterGrades>,
+}
+
+impl Published {
+    fn is_empty(&self) -> bool {
+        self.courses.is_empty() && self.overall.is_empty()
+    }
+}
+
+impl Snapshot {
+    fn published(&self, results: &ExamResults) -> Published {
+        Published {
+            courses: results
+                .courses
+                .iter()
+                .filter(|result| self.courses.get(&result.course.code) != Some(&result.grade))
+                .cloned()
+                .collect(),
+            overall: results
+                .overall
+                .iter()
+                .filter(|grades| {
+                    self.overall.get(&grades.semester) != Some(&(grades.sgpa, grades.cgpa))
+                })
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+/// Result alerts of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<ResultAlerts> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct ResultAlert;
+
+#[async_trait]
+impl JobHandler for ResultAlert {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut alerts) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let client = ctx.uncached_client(job.user_id()).await?;
+        let results = ExamResults::try_from(client.get_current_exam_result().await?)?;
+        let snapshot = Snapshot::from(&results);
+
+        let published = match alerts.snapshot {
+            Some(ref previous) if *previous == snapshot => return Ok(()),
+            Some(ref previous) => previous.published(&results),
+            None => {
+                trace!("Taking the first results snapshot of {}", job.user);
+                Published::default()
+            }
+        };
+
+        if !published.is_empty() {
+            let colour = ColourScheme::amity_colours().primary;
+
+            ctx.dm(job.user_id(), |m| {
+                m.embed(|e| {
+                    e.color(colour).title("Results published");
+
+                    let overall = published
+                        .overall
+                        .iter()
+                        .map(|grades| {
+                            format!(
+                                "Semester {}: **SGPA:** `{:.2}` | **CGPA:** `{:.2}`",
+                                grades.semester, grades.sgpa, grades.cgpa
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    if !overall.is_empty() {
+                        e.description(overall.join("\n"));
+                    }
+
+                    for result in published.courses.iter() {
+                        e.field(
+                            format!("{} {}", result.course.code, result.course.name),
+                            format!(
+                                "Grade: **{}** (`{}`) | Credits: `{}`",
+                                result.grade, result.grade_point, result.credits.acquired
+                            ),
+                            false,
+                        );
+                    }
+
+                    e
+                })
+            })
+            .await?;
+        }
+
+        alerts.snapshot = Some(snapshot);
+        settings.put(&key, &alerts).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amizone::model::{CourseRef, Credits};
+
+    fn result(code: &str, grade: &str) -> CourseResult {
+        CourseResult {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            grade: grade.to_string(),
+            grade_point: 0,
+            max_grade_point: 10,
+            credits: Credits {
+                acquired: 3,
+                effective: 3,
+                points: 0,
+            },
+            published: None,
+        }
+    }
+
+    fn grades(semester: usize, sgpa: f32, cgpa: f32) -> SemesterGrades {
+        SemesterGrades {
+            semester,
+            sgpa,
+            cgpa,
+        }
+    }
+
+    #[test]
+    fn only_new_results() {
+        let before = ExamResults {
+            courses: vec![result("A", "A+")],
+            overall: vec![grades(1, 9.0, 9.0)],
+        };
+        let snapshot = Snapshot::from(&before);
+        assert!(snapshot.published(&before).is_empty());
+
+        let after = ExamResults {
+            courses: vec![result("A", "A+"), result("B", "B"), result("C", "A")],
+            overall: vec![grades(1, 9.0, 9.0), grades(2, 8.0, 8.5)],
+        };
+        assert_eq!(
+            snapshot.published(&after),
+            Published {
+                courses: vec![result("B", "B"), result("C", "A")],
+                overall: vec![grades(2, 8.0, 8.5)],
+            }
+        );
+
+        // A re-evaluated grade counts as published again
+        let snapshot = Snapshot::from(&after);
+        let mut revised = after.clone();
+        revised.courses[1].grade = "B+".into();
+        assert_eq!(
+            snapshot.published(&revised).courses,
+            vec![result("B", "B+")]
+        );
+    }
+}
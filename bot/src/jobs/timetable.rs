@@ -0,0 +1,208 @@
+//! A DM when classes of today or tomorrow are cancelled, added, moved or change rooms or
+//! faculties.
+//!
+//! The schedules of both days are kept in the user's document and compared with the ones
+//! fetched every check, a day seen for the first time is only recorded.
+
+use std::collections::BTreeMap;
+
+use amizone::api::{
+    backend::AmizoneBackend,
+    store::Documents,
+    types::{AttendanceState, DatabaseConnection},
+};
+use amizone::model::{self, ClassSlot, CourseRef};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+use poise::serenity_prelude::CreateEmbed;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::schedule::ScheduleDiff;
+use crate::scheduler::{Job, JobContext, JobHandler, Schedule};
+use crate::{ColourScheme, Result};
+
+pub const KIND: &str = "timetable_changes";
+const COLLECTION: &str = "timetable_changes";
+
+pub const SCHEDULE: Schedule = Schedule::Every { secs: 30 * 60 };
+
+/// Last fetched schedules of a user by day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimetableSnapshots {
+    #[serde(default)]
+    days: BTreeMap<NaiveDate, Vec<StoredClass>>,
+}
+
+/// A [`ClassSlot`] as it is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredClass {
+    code: String,
+    name: String,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    faculty: String,
+    room: String,
+    attendance: i32,
+}
+
+impl From<&ClassSlot> for StoredClass {
+    fn from(value: &ClassSlot) -> Self {
+        Self {
+            code: value.course.code.clone(),
+            name: value.course.name.clone(),
+            start: value.start,
+            end: value.end,
+            faculty: value.faculty.clone(),
+            room: value.room.clone(),
+            attendance: value.attendance as i32,
+        }
+    }
+}
+
+impl StoredClass {
+    fn into_class(self) -> ClassSlot {
+        ClassSlot {
+            course: CourseRef {
+                code: self.code,
+                name: self.name,
+            },
+            start: self.start,
+            end: self.end,
+            faculty: self.faculty,
+            room: self.room,
+            attendance: AttendanceState::from(self.attendance),
+        }
+    }
+}
+
+impl TimetableSnapshots {
+    /// Records the classes of `date`, returns how they changed since they were last recorded.
+    /// An empty schedule doesn't replace classes already recorded, it's more likely Amizone
+    /// acting up than every class being cancelled, and would be reported as such twice.
+    fn update(&mut self, date: NaiveDate, classes: &[ClassSlot]) -> Option<ScheduleDiff> {
+        if classes.is_empty() && self.days.get(&date).map_or(false, |day| !day.is_empty()) {
+            return None;
+        }
+
+        let after = classes.iter().map(StoredClass::from).collect();
+        let before = self.days.insert(date, after)?;
+
+        let before = before
+            .into_iter()
+            .map(StoredClass::into_class)
+            .collect::<Vec<_>>();
+        Some(ScheduleDiff::new(date.into(), &before, classes)).filter(|diff| !diff.is_empty())
+    }
+
+    /// Forgets the days before `today`
+    fn prune(&mut self, today: NaiveDate) {
+        self.days.retain(|date, _| *date >= today);
+    }
+}
+
+/// Timetable snapshots of every user, keyed by user id
+pub fn settings(db: &DatabaseConnection) -> Documents<TimetableSnapshots> {
+    Documents::new(db.clone(), COLLECTION)
+}
+
+pub struct TimetableChanges;
+
+#[async_trait]
+impl JobHandler for TimetableChanges {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()> {
+        let settings = settings(&ctx.db);
+        let key = job.user.to_string();
+        let Some(mut snapshots) = settings.get(&key).await? else {
+            return Ok(());
+        };
+
+        let client = ctx.uncached_client(job.user_id()).await?;
+        let today = model::today();
+        let mut diffs = Vec::new();
+
+        for date in [today, today + Duration::days(1)] {
+            let classes = client
+                .get_class_schedule(date.into())
+                .await?
+                .into_iter()
+                .map(ClassSlot::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            diffs.extend(snapshots.update(date, &classes));
+        }
+        snapshots.prune(today);
+
+        if !diffs.is_empty() {
+            let colour = ColourScheme::amity_colours().tertiary;
+
+            ctx.dm(job.user_id(), |m| {
+                for diff in diffs.iter() {
+                    m.add_embed(|e| {
+                        *e = CreateEmbed::from(diff);
+                        e.color(colour)
+                    });
+                }
+                m
+            })
+            .await?;
+        }
+
+        settings.put(&key, &snapshots).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn class(code: &str, hour: u32) -> ClassSlot {
+        let start = model::ist()
+            .with_ymd_and_hms(2023, 5, 15, hour, 0, 0)
+            .unwrap();
+        ClassSlot {
+            course: CourseRef {
+                code: code.to_string(),
+                name: code.to_string(),
+            },
+            start,
+            end: start + Duration::hours(1),
+            faculty: String::from("Faculty"),
+            room: String::from("101"),
+            attendance: AttendanceState::Absent,
+        }
+    }
+
+    #[test]
+    fn snapshots() {
+        let day = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        let mut snapshots = TimetableSnapshots::default();
+
+        // Stored classes come back the same
+        let stored = StoredClass::from(&class("A", 9));
+        assert_eq!(stored.into_class(), class("A", 9));
+
+        assert!(snapshots.update(day, &[class("A", 9)]).is_none());
+        assert!(snapshots.update(day, &[class("A", 9)]).is_none());
+
+        let diff = snapshots
+            .update(day, &[class("A", 9), class("B", 10)])
+            .unwrap();
+        assert_eq!(diff.added, vec![class("B", 10)]);
+
+        // An empty schedule is ignored, the classes aren't reported as added back after it
+        assert!(snapshots.update(day, &[]).is_none());
+        assert!(snapshots
+            .update(day, &[class("A", 9), class("B", 10)])
+            .is_none());
+
+        snapshots.prune(day.succ_opt().unwrap());
+        assert!(snapshots.days.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod callbacks;
 pub mod commands;
 pub mod error;
+pub mod jobs;
+pub mod scheduler;
+pub mod server;
 pub mod util;
 use std::{collections::HashMap, env, sync::Arc};
 
@@ -9,10 +12,8 @@ use tokio::sync::Mutex;
 
 use std::time;
 
-use amizone::api::{
-    client::UserClient,
-    types::{AmizoneConnection, DatabaseConnection},
-};
+use amizone::api::cache::ResponseCache;
+use amizone::api::types::{AmizoneClient, AmizoneConnection, DatabaseConnection};
 use dotenv::dotenv;
 use poise::serenity_prelude::{self as serenity, Colour, UserId};
 
@@ -20,7 +21,7 @@ pub type Result<T> = std::result::Result<T, BotError>;
 pub type CommandResult = Result<()>;
 pub type Context<'a> = poise::Context<'a, Data, BotError>;
 type ApplicationContext<'a> = poise::ApplicationContext<'a, Data, BotError>;
-pub static IGNORE_CHECK: &[&str] = &["login", "help", "ping"];
+pub static IGNORE_CHECK: &[&str] = &["login", "help", "ping", "rotatekeys", "cachestats", "jobs"];
 
 pub struct ColourScheme {
     pub primary: Colour,
@@ -44,7 +45,12 @@ pub struct Data {
     pub dev_user_id: serenity::UserId,
     pub bot_user_id: serenity::UserId,
     pub colourscheme: ColourScheme,
-    pub users_cache: Arc<Mutex<HashMap<UserId, UserClient>>>,
+    pub users_cache: Arc<Mutex<HashMap<UserId, AmizoneClient>>>,
+    /// go-amizone responses of every user, if enabled with `AMIZONE_CACHE_SIZE`
+    pub response_cache: Option<Arc<ResponseCache>>,
+    /// Where calendar feeds are served, if enabled with `FEED_URL`
+    pub feed: Option<server::FeedConfig>,
+    pub scheduler: Arc<scheduler::Scheduler>,
 }
 
 pub struct Connections {
@@ -74,12 +80,29 @@ async fn main() {
                 commands::authentication::login::login(),
                 commands::authentication::logout::logout(),
                 commands::attendance::attendance(),
+                commands::plan::plan(),
+                commands::attendance_alerts::attendancealerts(),
+                commands::attendance_watch::attendancewatch(),
+                commands::attendance_history::attendancehistory(),
                 commands::exam::datesheet(),
+                commands::exam_reminders::examreminders(),
                 commands::courses::courses(),
                 commands::mac::wifimac(),
                 commands::profile::profile(),
                 commands::faculty_feedback::facultyfeedback(),
                 commands::schedule::schedule(),
+                commands::timetable_changes::timetablechanges(),
+                commands::week::week(),
+                commands::daily::daily(),
+                commands::reminders::reminders(),
+                commands::calendar::calendar(),
+                commands::feed::feed(),
+                commands::results::results(),
+                commands::result_alerts::resultalerts(),
+                commands::whatif::whatif(),
+                commands::dev::rotatekeys(),
+                commands::dev::cachestats(),
+                commands::dev::jobs(),
             ],
             ..Default::default()
         })
@@ -0,0 +1,621 @@
+//! Background jobs, recurring tasks of a user (a daily DM, polling for new results) that run
+//! on a timer instead of in answer to a command.
+//!
+//! Jobs are stored in the database under the `jobs` collection, keyed by kind and user, so
+//! they survive restarts. What a job does is up to the [`JobHandler`] registered for its kind,
+//! the [`Scheduler`] only decides when it runs: every tick it starts the jobs that are due,
+//! at most `JOB_CONCURRENCY` at a time so go-amizone isn't flooded.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use amizone::api::{
+    cache::ResponseCache,
+    store::Documents,
+    types::{AmizoneClient, AmizoneConnection, DatabaseConnection, DbOperationResult},
+    user::User,
+};
+use amizone::config::{env_var, parse_secs};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, FixedOffset, NaiveTime, Utc};
+use log::{debug, error, trace, warn};
+use poise::serenity_prelude::{CreateMessage, Http, Message, UserId};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use tokio::time::MissedTickBehavior;
+
+use crate::callbacks::user_client;
+use crate::error::BotError;
+use crate::Result;
+
+/// Collection jobs are stored in
+const JOBS_COLLECTION: &str = "jobs";
+
+/// Failed runs are retried sooner than scheduled this many times in a row
+const MAX_RETRIES: u32 = 5;
+/// First retry of a failed run is after this long, doubling every time
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    /// How often due jobs are looked for
+    pub tick: Duration,
+    /// Most jobs running at the same time
+    pub concurrency: usize,
+    /// Most a run of a recurring job is pushed back by, spreads out jobs made at the same time
+    pub jitter: Duration,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            tick: Duration::from_secs(30),
+            concurrency: 4,
+            jitter: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Reads `JOB_TICK`, `JOB_JITTER` (both in seconds, 0 turns the jitter off) and
+    /// `JOB_CONCURRENCY`, unset ones keep their defaults.
+    pub fn from_env() -> std::result::Result<Self, String> {
+        let mut config = Self::default();
+
+        if let Some(tick) = env_var("JOB_TICK") {
+            config.tick = parse_secs(&tick).map_err(|err| format!("`JOB_TICK`: {}", err))?;
+        }
+        if let Some(jitter) = env_var("JOB_JITTER") {
+            config.jitter = if jitter.trim() == "0" {
+                Duration::ZERO
+            } else {
+                parse_secs(&jitter).map_err(|err| format!("`JOB_JITTER`: {}", err))?
+            };
+        }
+        if let Some(concurrency) = env_var("JOB_CONCURRENCY") {
+            config.concurrency = match concurrency.parse() {
+                Ok(concurrency) if concurrency > 0 => concurrency,
+                _ => return Err(format!("Invalid `JOB_CONCURRENCY`: {}", concurrency)),
+            };
+        }
+
+        Ok(config)
+    }
+}
+
+/// When a job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Schedule {
+    /// Every so many seconds, give or take the jitter
+    Every { secs: u64 },
+    /// Every day at a local time, the offset is in minutes east of UTC
+    Daily { at: NaiveTime, offset_minutes: i32 },
+}
+
+impl Schedule {
+    /// First time the schedule comes up after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match *self {
+            Schedule::Every { secs } => after + ChronoDuration::seconds(secs.max(1) as i64),
+            Schedule::Daily { at, offset_minutes } => {
+                let offset = FixedOffset::east_opt(offset_minutes * 60)
+                    .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+
+                let today = after.with_timezone(&offset).date_naive().and_time(at);
+                let next = match today.and_local_timezone(offset).single() {
+                    Some(next) if next > after => next,
+                    _ => (today + ChronoDuration::days(1))
+                        .and_local_timezone(offset)
+                        .unwrap(),
+                };
+
+                next.with_timezone(&Utc)
+            }
+        }
+    }
+
+    /// Most a run is pushed back by out of `jitter`. Daily jobs run at the time asked for, and
+    /// frequent ones never drift by more than a tenth of their interval.
+    fn max_jitter(&self, jitter: Duration) -> Duration {
+        match *self {
+            Schedule::Every { secs } => jitter.min(Duration::from_secs(secs) / 10),
+            Schedule::Daily { .. } => Duration::ZERO,
+        }
+    }
+}
+
+/// A recurring task of a user, as it is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub user: u64,
+    /// Names the [`JobHandler`] that runs it
+    pub kind: String,
+    pub schedule: Schedule,
+    pub next_run: DateTime<Utc>,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Failed runs in a row
+    #[serde(default)]
+    pub failures: u32,
+}
+
+impl Job {
+    pub fn new(user: UserId, kind: &str, schedule: Schedule) -> Self {
+        Self {
+            user: user.0,
+            kind: kind.to_string(),
+            schedule,
+            next_run: schedule.next_after(Utc::now()),
+            paused: false,
+            last_run: None,
+            last_error: None,
+            failures: 0,
+        }
+    }
+
+    pub fn user_id(&self) -> UserId {
+        UserId(self.user)
+    }
+
+    pub fn key(&self) -> String {
+        job_key(self.user_id(), &self.kind)
+    }
+}
+
+fn job_key(user: UserId, kind: &str) -> String {
+    format!("{}:{}", kind, user.0)
+}
+
+/// Runs the jobs of one kind
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    /// Name jobs of this handler are stored with, has to stay the same across versions
+    fn kind(&self) -> &'static str;
+
+    async fn run(&self, ctx: &JobContext, job: &Job) -> Result<()>;
+}
+
+/// What jobs get to work with, the same connections the bot uses
+pub struct JobContext {
+    pub http: Arc<Http>,
+    pub db: DatabaseConnection,
+    pub amizone: AmizoneConnection,
+    pub response_cache: Option<Arc<ResponseCache>>,
+}
+
+impl JobContext {
+    /// go-amizone client of a logged in user, reads are served from the response cache if
+    /// there is one
+    pub async fn client(&self, user: UserId) -> Result<AmizoneClient> {
+        self.client_with_cache(user, self.response_cache.as_ref())
+            .await
+    }
+
+    /// go-amizone client of a logged in user that always asks go-amizone, for jobs looking
+    /// for changes or refreshing what they have right before using it
+    pub async fn uncached_client(&self, user: UserId) -> Result<AmizoneClient> {
+        self.client_with_cache(user, None).await
+    }
+
+    async fn client_with_cache(
+        &self,
+        user: UserId,
+        cache: Option<&Arc<ResponseCache>>,
+    ) -> Result<AmizoneClient> {
+        match User::from_id(user, &self.db).await? {
+            Some(user) => user_client(&user, &self.amizone, cache),
+            None => Err(BotError::Custom(format!("{} is not logged in", user))),
+        }
+    }
+
+    /// Sends a direct message to `user`
+    pub async fn dm<'a, F>(&self, user: UserId, f: F) -> Result<Message>
+    where
+        for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a> + Send,
+    {
+        let channel = user.create_dm_channel(&self.http).await?;
+        Ok(channel.send_message(&self.http, f).await?)
+    }
+}
+
+/// Marks a job as no longer running when dropped, so a run that panics doesn't keep the job
+/// from ever running again
+struct RunningJob {
+    scheduler: Arc<Scheduler>,
+    key: String,
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        self.scheduler.running.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// What the scheduler is up to, for the developer
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerStatus {
+    pub jobs: usize,
+    pub paused: usize,
+    pub running: usize,
+    /// Jobs whose last run failed
+    pub failing: usize,
+    pub by_kind: BTreeMap<String, usize>,
+    /// Runs and failed runs since the bot started
+    pub runs: u64,
+    pub failures: u64,
+    /// Jobs due next, soonest first
+    pub upcoming: Vec<Job>,
+}
+
+pub struct Scheduler {
+    jobs: Documents<Job>,
+    handlers: HashMap<&'static str, Arc<dyn JobHandler>>,
+    config: SchedulerConfig,
+    permits: Arc<Semaphore>,
+    /// Keys of the jobs running right now, a job isn't started again until it's done
+    running: Mutex<HashSet<String>>,
+    runs: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl Scheduler {
+    pub fn new(db: DatabaseConnection, config: SchedulerConfig) -> Self {
+        Self {
+            jobs: Documents::new(db, JOBS_COLLECTION),
+            handlers: HashMap::new(),
+            permits: Arc::new(Semaphore::new(config.concurrency)),
+            config,
+            running: Mutex::new(HashSet::new()),
+            runs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn register(&mut self, handler: impl JobHandler + 'static) -> &mut Self {
+        self.handlers.insert(handler.kind(), Arc::new(handler));
+        self
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Adds the job, replacing the user's job of the same kind
+    pub async fn schedule(&self, job: &Job) -> DbOperationResult<()> {
+        self.jobs.put(&job.key(), job).await
+    }
+
+    pub async fn get(&self, user: UserId, kind: &str) -> DbOperationResult<Option<Job>> {
+        self.jobs.get(&job_key(user, kind)).await
+    }
+
+    /// Returns the removed job, if there was one
+    pub async fn remove(&self, user: UserId, kind: &str) -> DbOperationResult<Option<Job>> {
+        self.jobs.remove(&job_key(user, kind)).await
+    }
+
+    /// Pauses or resumes a job, returns the updated job. A resumed job runs at the next time
+    /// its schedule comes up.
+    pub async fn set_paused(
+        &self,
+        user: UserId,
+        kind: &str,
+        paused: bool,
+    ) -> DbOperationResult<Option<Job>> {
+        let Some(mut job) = self.get(user, kind).await? else {
+            return Ok(None);
+        };
+
+        job.paused = paused;
+        if !paused {
+            job.next_run = job.schedule.next_after(Utc::now());
+        }
+        self.schedule(&job).await?;
+
+        Ok(Some(job))
+    }
+
+    /// Every job of `user`
+    pub async fn jobs_of(&self, user: UserId) -> DbOperationResult<Vec<Job>> {
+        Ok(self
+            .jobs
+            .list()
+            .await?
+            .into_iter()
+            .map(|(_, job)| job)
+            .filter(|job| job.user == user.0)
+            .collect())
+    }
+
+    /// Removes every job of `user`, returns how many there were
+    pub async fn remove_user(&self, user: UserId) -> DbOperationResult<usize> {
+        let jobs = self.jobs_of(user).await?;
+        for job in jobs.iter() {
+            self.jobs.remove(&job.key()).await?;
+        }
+        Ok(jobs.len())
+    }
+
+    pub async fn status(&self) -> DbOperationResult<SchedulerStatus> {
+        let jobs = self.jobs.list().await?;
+        let mut status = SchedulerStatus {
+            jobs: jobs.len(),
+            running: self.running.lock().unwrap().len(),
+            runs: self.runs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+
+        for (_, job) in jobs {
+            *status.by_kind.entry(job.kind.clone()).or_default() += 1;
+            if job.paused {
+                status.paused += 1;
+                continue;
+            }
+            if job.failures > 0 {
+                status.failing += 1;
+            }
+            status.upcoming.push(job);
+        }
+
+        status.upcoming.sort_by_key(|job| job.next_run);
+        status.upcoming.truncate(5);
+
+        Ok(status)
+    }
+
+    /// Runs due jobs in the background every tick, for as long as the bot runs
+    pub fn start(self: &Arc<Self>, ctx: JobContext) {
+        let scheduler = self.clone();
+        let ctx = Arc::new(ctx);
+
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(scheduler.config.tick);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticks.tick().await;
+                if let Err(err) = scheduler.tick(&ctx).await {
+                    error!("Failed to look for due jobs: {}", err);
+                }
+            }
+        });
+    }
+
+    async fn tick(self: &Arc<Self>, ctx: &Arc<JobContext>) -> DbOperationResult<()> {
+        let now = Utc::now();
+
+        for (key, job) in self.jobs.list().await? {
+            if job.paused || job.next_run > now {
+                continue;
+            }
+            if !self.running.lock().unwrap().insert(key.clone()) {
+                continue;
+            }
+
+            // Waits here while too many jobs run, the rest of the due ones start as they finish
+            let permit = self
+                .permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("Job semaphore closed");
+            let (scheduler, ctx) = (self.clone(), ctx.clone());
+            let running = RunningJob {
+                scheduler: self.clone(),
+                key,
+            };
+
+            tokio::spawn(async move {
+                scheduler.run(&ctx, job).await;
+                drop((running, permit));
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn run(&self, ctx: &JobContext, job: Job) {
+        let key = job.key();
+        let Some(handler) = self.handlers.get(job.kind.as_str()) else {
+            warn!("No handler for job {}, skipping it", key);
+            return;
+        };
+
+        let result = match User::from_id(job.user, &ctx.db).await {
+            Ok(Some(_)) => {
+                trace!("Running job {}", key);
+                handler.run(ctx, &job).await
+            }
+            Ok(None) => {
+                debug!("{} logged out, removing job {}", job.user, key);
+                if let Err(err) = self.jobs.remove(&key).await {
+                    error!("Failed to remove job {}: {}", key, err);
+                }
+                return;
+            }
+            Err(err) => Err(err.into()),
+        };
+
+        // The job might have been changed (or removed) while it ran, only the run is recorded
+        let mut stored = match self.jobs.get(&key).await {
+            Ok(Some(stored)) => stored,
+            Ok(None) => return,
+            Err(err) => {
+                error!("Failed to reload job {}: {}", key, err);
+                return;
+            }
+        };
+
+        let now = Utc::now();
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        stored.last_run = Some(now);
+
+        match result {
+            Ok(()) => {
+                stored.failures = 0;
+                stored.last_error = None;
+                stored.next_run = self.next_run(&stored.schedule, now);
+            }
+            Err(err) => {
+                warn!("Job {} failed: {}", key, err);
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                stored.failures += 1;
+                stored.last_error = Some(err.to_string());
+                stored.next_run = self.retry(&stored, now);
+            }
+        }
+
+        if let Err(err) = self.jobs.put(&key, &stored).await {
+            error!("Failed to save job {}: {}", key, err);
+        }
+    }
+
+    fn next_run(&self, schedule: &Schedule, now: DateTime<Utc>) -> DateTime<Utc> {
+        let next = schedule.next_after(now);
+        let max_jitter = schedule.max_jitter(self.config.jitter);
+        if max_jitter.is_zero() {
+            return next;
+        }
+
+        let jitter = rand::thread_rng().gen_range(Duration::ZERO..=max_jitter);
+        next + ChronoDuration::from_std(jitter).unwrap_or_else(|_| ChronoDuration::zero())
+    }
+
+    /// Failed runs are retried with exponential backoff, unless the job comes up sooner anyway
+    /// or it has failed too many times in a row
+    fn retry(&self, job: &Job, now: DateTime<Utc>) -> DateTime<Utc> {
+        let next = self.next_run(&job.schedule, now);
+        if job.failures > MAX_RETRIES {
+            return next;
+        }
+
+        let delay = RETRY_DELAY
+            .saturating_mul(1 << (job.failures - 1))
+            .min(MAX_RETRY_DELAY);
+        let retry =
+            now + ChronoDuration::from_std(delay).unwrap_or_else(|_| ChronoDuration::zero());
+
+        retry.min(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn daily_schedule() {
+        let schedule = Schedule::Daily {
+            at: NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+            offset_minutes: 330,
+        };
+
+        // 01:00 UTC is 06:30 IST, same day
+        let after = Utc.with_ymd_and_hms(2023, 5, 15, 1, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(after),
+            Utc.with_ymd_and_hms(2023, 5, 15, 2, 0, 0).unwrap()
+        );
+
+        // Right at the time, it's the next day
+        let after = Utc.with_ymd_and_hms(2023, 5, 15, 2, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(after),
+            Utc.with_ymd_and_hms(2023, 5, 16, 2, 0, 0).unwrap()
+        );
+
+        // 20:00 UTC is already the 16th in IST
+        let after = Utc.with_ymd_and_hms(2023, 5, 15, 20, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(after),
+            Utc.with_ymd_and_hms(2023, 5, 16, 2, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let jitter = Duration::from_secs(60);
+
+        assert_eq!(Schedule::Every { secs: 60 * 60 }.max_jitter(jitter), jitter);
+        assert_eq!(
+            Schedule::Every { secs: 60 }.max_jitter(jitter),
+            Duration::from_secs(6)
+        );
+        assert!(Schedule::Daily {
+            at: NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+            offset_minutes: 0,
+        }
+        .max_jitter(jitter)
+        .is_zero());
+    }
+
+    #[test]
+    fn jobs_round_trip() {
+        let job = Job::new(UserId(42), "test", Schedule::Every { secs: 600 });
+        let json = serde_json::to_string(&job).unwrap();
+
+        assert!(json.contains(r#""schedule":{"type":"every","secs":600}"#));
+        assert_eq!(serde_json::from_str::<Job>(&json).unwrap(), job);
+        assert_eq!(job.key(), "test:42");
+    }
+
+    #[test]
+    fn retries_back_off() {
+        let scheduler = Scheduler::new(
+            Arc::new(amizone::api::store::MemoryStore::default()),
+            SchedulerConfig {
+                jitter: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+        let now = Utc.with_ymd_and_hms(2023, 5, 15, 0, 0, 0).unwrap();
+        let mut job = Job::new(UserId(42), "test", Schedule::Every { secs: 60 * 60 * 24 });
+
+        job.failures = 1;
+        assert_eq!(scheduler.retry(&job, now), now + ChronoDuration::minutes(1));
+        job.failures = 3;
+        assert_eq!(scheduler.retry(&job, now), now + ChronoDuration::minutes(4));
+        job.failures = MAX_RETRIES + 1;
+        assert_eq!(scheduler.retry(&job, now), now + ChronoDuration::days(1));
+
+        // Never later than the next scheduled run
+        job.schedule = Schedule::Every { secs: 90 };
+        job.failures = 3;
+        assert_eq!(
+            scheduler.retry(&job, now),
+            now + ChronoDuration::seconds(90)
+        );
+    }
+
+    #[tokio::test]
+    async fn panicking_run_stops_running() {
+        let scheduler = Arc::new(Scheduler::new(
+            Arc::new(amizone::api::store::MemoryStore::default()),
+            SchedulerConfig::default(),
+        ));
+        scheduler.running.lock().unwrap().insert("test:42".into());
+
+        let running = RunningJob {
+            scheduler: scheduler.clone(),
+            key: "test:42".into(),
+        };
+        let run = tokio::spawn(async move {
+            let _running = running;
+            panic!("handler panicked");
+        });
+
+        assert!(run.await.unwrap_err().is_panic());
+        assert!(scheduler.running.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! HTTP server for calendar subscription feeds, every user with a feed token gets their
+//! classes and exams as an iCalendar feed at `/feed/<token>.ics`, generated when a calendar
+//! app polls it.
+
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use amizone::api::{
+    cache::ResponseCache,
+    types::{AmizoneConnection, DatabaseConnection},
+    user::User,
+};
+use amizone::model;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::Duration;
+use log::{error, info, trace};
+
+use crate::callbacks::user_client;
+use crate::commands::calendar::build_calendar;
+
+/// Days of classes before today in a feed, so the current week stays whole
+const FEED_DAYS_BEFORE: i64 = 7;
+/// Days of classes after today in a feed
+const FEED_DAYS_AFTER: i64 = 28;
+
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    /// Where the server listens
+    pub listen: SocketAddr,
+    /// Base url the server is reachable at from outside, feed urls are built on it
+    pub public_url: String,
+}
+
+impl FeedConfig {
+    /// Reads `FEED_URL` and `FEED_LISTEN` (defaults to `0.0.0.0:443`), feeds are disabled
+    /// unless `FEED_URL` is set.
+    pub fn from_env() -> std::result::Result<Option<Self>, String> {
+        let public_url = match env::var("FEED_URL") {
+            Ok(url) if !url.trim().is_empty() => url.trim().trim_end_matches('/').to_string(),
+            _ => return Ok(None),
+        };
+
+        if !public_url.starts_with("http://") && !public_url.starts_with("https://") {
+            return Err(format!(
+                "`FEED_URL` should be an http(s) url, got {}",
+                public_url
+            ));
+        }
+
+        let listen = match env::var("FEED_LISTEN") {
+            Ok(addr) if !addr.trim().is_empty() => addr
+                .trim()
+                .parse()
+                .map_err(|err| format!("Invalid `FEED_LISTEN`: {}", err))?,
+            _ => SocketAddr::from(([0, 0, 0, 0], 443)),
+        };
+
+        Ok(Some(Self { listen, public_url }))
+    }
+
+    /// Url of the feed with `token`
+    pub fn feed_url(&self, token: &str) -> String {
+        format!("{}/feed/{}.ics", self.public_url, token)
+    }
+}
+
+/// What the handlers need, the same connections the bot uses
+pub struct FeedState {
+    pub amizone: AmizoneConnection,
+    pub db: DatabaseConnection,
+    pub response_cache: Option<Arc<ResponseCache>>,
+}
+
+/// Serves feeds until the process exits
+pub async fn serve(config: FeedConfig, state: FeedState) {
+    let app = Router::new()
+        .route("/feed/:token", get(feed))
+        .with_state(Arc::new(state));
+
+    info!("Serving calendar feeds on {}", config.listen);
+    if let Err(err) = axum::Server::bind(&config.listen)
+        .serve(app.into_make_service())
+        .await
+    {
+        error!("Calendar feed server stopped: {}", err);
+    }
+}
+
+async fn feed(State(state): State<Arc<FeedState>>, Path(file): Path<String>) -> Response {
+    let token = file.strip_suffix(".ics").unwrap_or(&file);
+
+    let user = match User::from_feed_token(token, &state.db).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!("Failed to look up a feed token: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    trace!("Generating the calendar feed of {}", user.id());
+
+    let client = match user_client(&user, &state.amizone, state.response_cache.as_ref()) {
+        Ok(client) => client,
+        Err(err) => {
+            error!(
+                "Unreadable credentials for the feed of {}: {}",
+                user.id(),
+                err
+            );
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let today = model::today();
+    let start = today - Duration::days(FEED_DAYS_BEFORE);
+    let end = today + Duration::days(FEED_DAYS_AFTER);
+
+    match build_calendar(&*client, start, end).await {
+        Ok(calendar) => (
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (header::CACHE_CONTROL, "private, max-age=900"),
+            ],
+            calendar.to_string(),
+        )
+            .into_response(),
+        Err(err) => {
+            error!(
+                "Failed to generate the calendar feed of {}: {}",
+                user.id(),
+                err
+            );
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+    }
+}
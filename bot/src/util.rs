@@ -1,3 +1,4 @@
+use chrono::{FixedOffset, NaiveTime};
 use poise::serenity_prelude::{self as serenity, CreateEmbed};
 
 use crate::error::BotError;
@@ -78,3 +79,118 @@ where
 
     Ok(())
 }
+
+/// Parses a time of day like `7:30`, `19:05`, `7:30pm` or `7 am`
+pub fn parse_time(time: &str) -> Option<NaiveTime> {
+    let mut time = time.trim().to_ascii_uppercase().replace(' ', "");
+    if !time.contains(':') {
+        let hour_end = time
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(time.len());
+        time.insert_str(hour_end, ":00");
+    }
+
+    ["%H:%M", "%I:%M%p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(&time, format).ok())
+}
+
+/// Parses a UTC offset like `+05:30`, `-4`, `UTC+5:30`, `UTC` or `IST`
+pub fn parse_utc_offset(offset: &str) -> Option<FixedOffset> {
+    let offset = offset.trim().to_ascii_uppercase();
+    let offset = match offset.as_str() {
+        "IST" => return FixedOffset::east_opt(330 * 60),
+        "UTC" | "GMT" | "Z" => return FixedOffset::east_opt(0),
+        offset => offset
+            .strip_prefix("UTC")
+            .or_else(|| offset.strip_prefix("GMT"))
+            .unwrap_or(offset),
+    };
+
+    let (sign, offset) = if let Some(offset) = offset.strip_prefix('+') {
+        (1, offset)
+    } else if let Some(offset) = offset.strip_prefix('-') {
+        (-1, offset)
+    } else {
+        return None;
+    };
+
+    let (hours, minutes) = match offset.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?),
+        None => (offset.parse::<i32>().ok()?, 0),
+    };
+
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 60 + minutes) * 60)
+}
+
+/// Offset in the form `+05:30`
+pub fn format_utc_offset(offset: FixedOffset) -> String {
+    let minutes = offset.local_minus_utc() / 60;
+    format!(
+        "{}{:02}:{:02}",
+        if minutes < 0 { '-' } else { '+' },
+        minutes.abs() / 60,
+        minutes.abs() % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, minute, 0)
+    }
+
+    fn offset(minutes: i32) -> Option<FixedOffset> {
+        FixedOffset::east_opt(minutes * 60)
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(parse_time("7:30"), time(7, 30));
+        assert_eq!(parse_time(" 19:05 "), time(19, 5));
+        assert_eq!(parse_time("7"), time(7, 0));
+        assert_eq!(parse_time("7:30pm"), time(19, 30));
+        assert_eq!(parse_time("7 am"), time(7, 0));
+        assert_eq!(parse_time("7 PM"), time(19, 0));
+        assert_eq!(parse_time("12am"), time(0, 0));
+        assert_eq!(parse_time("12:15pm"), time(12, 15));
+
+        for bad in ["", "noon", "24:00", "7:60", "13pm", "0am", "7:30xm", "-7"] {
+            assert_eq!(parse_time(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn utc_offsets() {
+        assert_eq!(parse_utc_offset("+05:30"), offset(330));
+        assert_eq!(parse_utc_offset("-4"), offset(-240));
+        assert_eq!(parse_utc_offset("UTC+5:30"), offset(330));
+        assert_eq!(parse_utc_offset("gmt-3:30"), offset(-210));
+        assert_eq!(parse_utc_offset(" utc "), offset(0));
+        assert_eq!(parse_utc_offset("Z"), offset(0));
+        assert_eq!(parse_utc_offset("IST"), offset(330));
+        assert_eq!(parse_utc_offset("+14"), offset(14 * 60));
+
+        for bad in ["", "5:30", "+15", "+5:60", "UTC+", "+-5", "+five", "PST"] {
+            assert_eq!(parse_utc_offset(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn formatted_offsets_parse_back() {
+        assert_eq!(format_utc_offset(offset(330).unwrap()), "+05:30");
+        assert_eq!(format_utc_offset(offset(-210).unwrap()), "-03:30");
+        assert_eq!(format_utc_offset(offset(0).unwrap()), "+00:00");
+
+        for minutes in [-720, -210, -60, 0, 45, 330, 345, 840] {
+            let offset = offset(minutes).unwrap();
+            assert_eq!(parse_utc_offset(&format_utc_offset(offset)), Some(offset));
+        }
+    }
+}